@@ -1,7 +1,11 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
 use clap::Parser;
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
-use tree234_rs::Tree234;
+use tree234_rs::{Tree234, Tree234Arena};
 
 /// Insert, Remove, Fetch items from a 234-tree
 #[derive(Parser, Debug)]
@@ -18,8 +22,94 @@ struct Args {
     /// Number of bits for keys
     #[arg(short, long, default_value_t = 20)]
     bits: usize,
+
+    /// Number of full traversals to time once the tree is built
+    #[arg(short, long, default_value_t = 20)]
+    passes: u64,
+
+    /// Run the workload on the slab-backed tree
+    #[arg(short, long)]
+    arena: bool,
+}
+
+// The operations the mixed workload needs, so that it runs on either tree.
+trait Map {
+    fn get(&self, key: &u64) -> Option<&u64>;
+    fn insert(&mut self, key: u64, value: u64) -> Option<u64>;
+    fn remove(&mut self, key: &u64) -> Option<u64>;
+    fn size(&self) -> usize;
+    fn visit_items(&self, visitor: &mut dyn FnMut(&u64, &u64));
+}
+
+impl Map for Tree234<u64, u64> {
+    fn get(&self, key: &u64) -> Option<&u64> {
+        Tree234::get(self, key).map(|item| &item.1)
+    }
+
+    fn insert(&mut self, key: u64, value: u64) -> Option<u64> {
+        Tree234::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &u64) -> Option<u64> {
+        Tree234::remove(self, key)
+    }
+
+    fn size(&self) -> usize {
+        Tree234::size(self)
+    }
+
+    fn visit_items(&self, visitor: &mut dyn FnMut(&u64, &u64)) {
+        self.iter().for_each(|item| visitor(&item.0, &item.1))
+    }
 }
 
+impl Map for Tree234Arena<u64, u64> {
+    fn get(&self, key: &u64) -> Option<&u64> {
+        Tree234Arena::get(self, key)
+    }
+
+    fn insert(&mut self, key: u64, value: u64) -> Option<u64> {
+        Tree234Arena::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &u64) -> Option<u64> {
+        Tree234Arena::remove(self, key)
+    }
+
+    fn size(&self) -> usize {
+        Tree234Arena::size(self)
+    }
+
+    fn visit_items(&self, visitor: &mut dyn FnMut(&u64, &u64)) {
+        self.iter().for_each(|(key, value)| visitor(key, value))
+    }
+}
+
+// Counts heap allocations so that the cost of restructuring the tree shows
+// up alongside the timings.
+struct Counting;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
 #[derive(Debug)]
 struct Counts {
     search_count: u64,
@@ -30,13 +120,21 @@ struct Counts {
     remove_successes: u64,
 }
 
-pub fn main() {
-    let args = Args::parse();
+fn report(name: &str, items: u64, started: Instant) {
+    let elapsed = started.elapsed().as_secs_f64();
+    println!(
+        "{:>10}: {:.1} M items/s",
+        name,
+        (items as f64) / elapsed / 1.0e6
+    );
+}
 
+// Run the mixed search/insert/remove workload, reporting its throughput and
+// how many allocations it made.
+fn churn<M: Map>(tree: &mut M, args: &Args) {
     let mut rng = StdRng::seed_from_u64(args.seed);
     let n = args.count;
     let m: u64 = (1 << args.bits) - 1;
-    let mut tree: Tree234<u64, u64> = Tree234::new();
     let mut counts = Counts {
         search_count: 0,
         search_successes: 0,
@@ -45,6 +143,8 @@ pub fn main() {
         remove_count: 0,
         remove_successes: 0,
     };
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let started = Instant::now();
     for i in 0..n {
         let u = rng.gen::<u64>() & m;
         let s = tree.get(&u);
@@ -66,11 +166,100 @@ pub fn main() {
             }
         }
     }
+    let elapsed = started.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
     let z = tree.size();
     let mut s = 0;
-    for item in tree.iter() {
-        s += n - item.1;
-    }
+    tree.visit_items(&mut |_key, value| s += n - value);
     println!("{:?}", counts);
-    println!("mean age = {}", (s as f64)/(z as f64));
+    println!("mean age = {}", (s as f64) / (z as f64));
+    println!(
+        "allocations = {} ({:.2} per insert/remove)",
+        allocations,
+        (allocations as f64) / (n as f64)
+    );
+    println!(
+        "{:>10}: {:.1} M items/s",
+        "mixed",
+        (n as f64) / elapsed.as_secs_f64() / 1.0e6
+    );
+}
+
+// Time lookups of random keys drawn from the same range as the workload's.
+fn lookups<M: Map>(tree: &M, args: &Args) {
+    let mut rng = StdRng::seed_from_u64(args.seed + 1);
+    let m: u64 = (1 << args.bits) - 1;
+    let mut found = 0u64;
+    let started = Instant::now();
+    for _i in 0..args.count {
+        if tree.get(&(rng.gen::<u64>() & m)).is_some() {
+            found += 1;
+        }
+    }
+    report("get", args.count, started);
+    println!("found = {}", found);
+}
+
+fn report_drop<M>(tree: M) {
+    let started = Instant::now();
+    drop(tree);
+    println!(
+        "{:>10}: {:.1} ms",
+        "drop",
+        started.elapsed().as_secs_f64() * 1.0e3
+    );
+}
+
+pub fn main() {
+    let args = Args::parse();
+
+    if args.arena {
+        let mut tree: Tree234Arena<u64, u64> = Tree234Arena::new();
+        churn(&mut tree, &args);
+        lookups(&tree, &args);
+        let items = (tree.size() as u64) * args.passes;
+        let mut check = 0u64;
+        let started = Instant::now();
+        for _pass in 0..args.passes {
+            for (_key, value) in tree.iter() {
+                check = check.wrapping_add(*value);
+            }
+        }
+        report("iter", items, started);
+        println!("checksum = {}", check);
+        report_drop(tree);
+        return;
+    }
+
+    let mut tree: Tree234<u64, u64> = Tree234::new();
+    churn(&mut tree, &args);
+    lookups(&tree, &args);
+    let z = tree.size();
+
+    // Traversal throughput: `iter` walks a fixed-size stack of nodes, while
+    // `iter_mut` expands nodes through a heap-allocated queue, and `visit`
+    // recurses.
+    let items = (z as u64) * args.passes;
+    let mut check = 0u64;
+    let started = Instant::now();
+    for _pass in 0..args.passes {
+        for item in tree.iter() {
+            check = check.wrapping_add(item.1);
+        }
+    }
+    report("iter", items, started);
+    let started = Instant::now();
+    for _pass in 0..args.passes {
+        for (_key, value) in tree.iter_mut() {
+            check = check.wrapping_add(*value);
+        }
+    }
+    report("iter_mut", items, started);
+    let started = Instant::now();
+    for _pass in 0..args.passes {
+        tree.visit(&mut |item| check = check.wrapping_add(item.1));
+    }
+    report("visit", items, started);
+    println!("checksum = {}", check);
+    report_drop(tree);
 }
@@ -0,0 +1,766 @@
+use std::borrow::Borrow;
+use std::mem::MaybeUninit;
+
+use crate::tree234::{Item, MAX_DEPTH};
+
+// The index standing for an empty subtree.
+const NIL: u32 = u32::MAX;
+
+// A node with up to three items, whose keys, values and children are kept
+// in separate arrays so that a search reads keys alone. The first `len` keys
+// and values are initialised. A node left with no items during a removal is
+// a hole standing in for its only child until its parent repairs it.
+struct Node<K, V> {
+    len: u8,
+    keys: [MaybeUninit<K>; 3],
+    values: [MaybeUninit<V>; 3],
+    children: [u32; 4],
+}
+
+impl<K, V> Node<K, V> {
+    fn two(item: Item<K, V>, lhs: u32, rhs: u32) -> Node<K, V> {
+        let (key, value) = item;
+        Node {
+            len: 1,
+            keys: [
+                MaybeUninit::new(key),
+                MaybeUninit::uninit(),
+                MaybeUninit::uninit(),
+            ],
+            values: [
+                MaybeUninit::new(value),
+                MaybeUninit::uninit(),
+                MaybeUninit::uninit(),
+            ],
+            children: [lhs, rhs, NIL, NIL],
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    fn keys(&self) -> &[K] {
+        // SAFETY: the first `len` keys are initialised.
+        unsafe { std::slice::from_raw_parts(self.keys.as_ptr().cast(), self.len()) }
+    }
+
+    fn values(&self) -> &[V] {
+        // SAFETY: the first `len` values are initialised.
+        unsafe { std::slice::from_raw_parts(self.values.as_ptr().cast(), self.len()) }
+    }
+
+    fn keys_mut(&mut self) -> &mut [K] {
+        // SAFETY: as for `keys`.
+        unsafe { std::slice::from_raw_parts_mut(self.keys.as_mut_ptr().cast(), self.len()) }
+    }
+
+    fn values_mut(&mut self) -> &mut [V] {
+        // SAFETY: as for `values`.
+        unsafe { std::slice::from_raw_parts_mut(self.values.as_mut_ptr().cast(), self.len()) }
+    }
+
+    fn entry(&self, i: usize) -> (&K, &V) {
+        (&self.keys()[i], &self.values()[i])
+    }
+
+    // The index of the key equal to `key`, or else of the child to descend
+    // into.
+    fn search<Q: Ord + ?Sized>(&self, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+    {
+        let keys = self.keys();
+        for (i, k) in keys.iter().enumerate() {
+            match key.cmp(k.borrow()) {
+                std::cmp::Ordering::Less => return Err(i),
+                std::cmp::Ordering::Equal => return Ok(i),
+                std::cmp::Ordering::Greater => {}
+            }
+        }
+        Err(keys.len())
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children[0] == NIL
+    }
+
+    // `Parts::insert` and `Parts::remove`, shuffled in place.
+    fn insert(&mut self, i: usize, item: Item<K, V>, j: usize, child: u32) {
+        let n = self.len();
+        self.keys[n].write(item.0);
+        self.keys[i..=n].rotate_right(1);
+        self.values[n].write(item.1);
+        self.values[i..=n].rotate_right(1);
+        self.children[n + 1] = child;
+        self.children[j..=n + 1].rotate_right(1);
+        self.len += 1;
+    }
+
+    fn remove(&mut self, i: usize, j: usize) -> (Item<K, V>, u32) {
+        let n = self.len() - 1;
+        self.keys[i..=n].rotate_left(1);
+        self.values[i..=n].rotate_left(1);
+        self.children[j..=n + 1].rotate_left(1);
+        self.len -= 1;
+        // SAFETY: slot `n` was initialised, and is no longer counted by `len`.
+        let key = unsafe { self.keys[n].assume_init_read() };
+        // SAFETY: as for the key.
+        let value = unsafe { self.values[n].assume_init_read() };
+        let child = std::mem::replace(&mut self.children[n + 1], NIL);
+        ((key, value), child)
+    }
+
+    fn replace(&mut self, i: usize, item: Item<K, V>) -> Item<K, V> {
+        let key = std::mem::replace(&mut self.keys_mut()[i], item.0);
+        let value = std::mem::replace(&mut self.values_mut()[i], item.1);
+        (key, value)
+    }
+}
+
+impl<K: Clone, V: Clone> Clone for Node<K, V> {
+    fn clone(&self) -> Node<K, V> {
+        let mut node = Node {
+            len: 0,
+            keys: [const { MaybeUninit::uninit() }; 3],
+            values: [const { MaybeUninit::uninit() }; 3],
+            children: self.children,
+        };
+        for (i, (key, value)) in self.keys().iter().zip(self.values()).enumerate() {
+            node.keys[i].write(key.clone());
+            node.values[i].write(value.clone());
+            node.len += 1;
+        }
+        node
+    }
+}
+
+impl<K, V> Drop for Node<K, V> {
+    fn drop(&mut self) {
+        // SAFETY: the slices cover exactly the initialised keys and values,
+        // which are not used again.
+        unsafe {
+            std::ptr::drop_in_place(self.keys_mut());
+            std::ptr::drop_in_place(self.values_mut());
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Slot<K, V> {
+    Occupied(Node<K, V>),
+    // A free slot, linked to the next one.
+    Vacant(u32),
+}
+
+enum Inserted<K, V> {
+    Replaced(V),
+    Added,
+    Split(Item<K, V>, u32),
+}
+
+// A 2-3-4 tree whose nodes live in a single slab and refer to each other by
+// `u32` index. Freed nodes are chained into a free list and reused, and the
+// whole tree is released at once rather than node by node.
+#[derive(Clone)]
+pub struct Tree234Arena<K: Eq + Ord, V> {
+    slots: Vec<Slot<K, V>>,
+    free: u32,
+    root: u32,
+    size: usize,
+}
+
+impl<K: Eq + Ord, V> Tree234Arena<K, V> {
+    pub fn new() -> Tree234Arena<K, V> {
+        Tree234Arena {
+            slots: Vec::new(),
+            free: NIL,
+            root: NIL,
+            size: 0,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        let (at, i) = self.find(key)?;
+        Some(&self.node(at).values()[i])
+    }
+
+    pub fn get_key_value<Q: Ord + ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+    {
+        let (at, i) = self.find(key)?;
+        Some(self.node(at).entry(i))
+    }
+
+    pub fn get_mut<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        let (at, i) = self.find(key)?;
+        Some(&mut self.node_mut(at).values_mut()[i])
+    }
+
+    pub fn contains_key<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.find(key).is_some()
+    }
+
+    pub fn first(&self) -> Option<(&K, &V)> {
+        let mut at = self.root;
+        while at != NIL {
+            let node = self.node(at);
+            if node.is_leaf() {
+                return Some(node.entry(0));
+            }
+            at = node.children[0];
+        }
+        None
+    }
+
+    pub fn last(&self) -> Option<(&K, &V)> {
+        let mut at = self.root;
+        while at != NIL {
+            let node = self.node(at);
+            if node.is_leaf() {
+                return Some(node.entry(node.len() - 1));
+            }
+            at = node.children[node.len()];
+        }
+        None
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.insert_in(self.root, key, value) {
+            Inserted::Replaced(replaced) => return Some(replaced),
+            Inserted::Added => {}
+            Inserted::Split(item, rhs) => {
+                self.root = self.alloc(Node::two(item, self.root, rhs));
+            }
+        }
+        self.size += 1;
+        None
+    }
+
+    pub fn remove<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        self.remove_entry(key).map(|item| item.1)
+    }
+
+    pub fn remove_entry<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<Item<K, V>>
+    where
+        K: Borrow<Q>,
+    {
+        let (result, reduced) = self.remove_in(self.root, key);
+        self.settle_root(reduced);
+        if result.is_some() {
+            self.size -= 1;
+        }
+        result
+    }
+
+    pub fn pop_first(&mut self) -> Option<Item<K, V>> {
+        if self.root == NIL {
+            return None;
+        }
+        let (item, reduced) = self.remove_first_in(self.root);
+        self.settle_root(reduced);
+        self.size -= 1;
+        Some(item)
+    }
+
+    pub fn pop_last(&mut self) -> Option<Item<K, V>> {
+        if self.root == NIL {
+            return None;
+        }
+        let (item, reduced) = self.remove_last_in(self.root);
+        self.settle_root(reduced);
+        self.size -= 1;
+        Some(item)
+    }
+
+    // Constant time when neither K nor V needs dropping; otherwise one pass
+    // over the slab drops the items, with no tree walk. The slab keeps its
+    // capacity.
+    pub fn clear(&mut self) {
+        if std::mem::needs_drop::<K>() || std::mem::needs_drop::<V>() {
+            self.slots.clear();
+        } else {
+            // SAFETY: the slots cut off hold nothing that needs dropping.
+            unsafe { self.slots.set_len(0) };
+        }
+        self.free = NIL;
+        self.root = NIL;
+        self.size = 0;
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut front = Edge::new();
+        let mut back = Edge::new();
+        front.push_first(self, self.root);
+        back.push_last(self, self.root);
+        Iter {
+            tree: self,
+            front,
+            back,
+            remaining: self.size,
+        }
+    }
+
+    fn node(&self, at: u32) -> &Node<K, V> {
+        match &self.slots[at as usize] {
+            Slot::Occupied(node) => node,
+            Slot::Vacant(_) => panic!("vacant slot {}", at),
+        }
+    }
+
+    fn node_mut(&mut self, at: u32) -> &mut Node<K, V> {
+        match &mut self.slots[at as usize] {
+            Slot::Occupied(node) => node,
+            Slot::Vacant(_) => panic!("vacant slot {}", at),
+        }
+    }
+
+    fn alloc(&mut self, node: Node<K, V>) -> u32 {
+        if self.free == NIL {
+            assert!(self.slots.len() < NIL as usize, "arena is full");
+            self.slots.push(Slot::Occupied(node));
+            return (self.slots.len() - 1) as u32;
+        }
+        let at = self.free;
+        match std::mem::replace(&mut self.slots[at as usize], Slot::Occupied(node)) {
+            Slot::Vacant(next) => self.free = next,
+            Slot::Occupied(_) => unreachable!(),
+        }
+        at
+    }
+
+    fn release(&mut self, at: u32) {
+        self.slots[at as usize] = Slot::Vacant(self.free);
+        self.free = at;
+    }
+
+    // The node and index holding the given key.
+    fn find<Q: Ord + ?Sized>(&self, key: &Q) -> Option<(u32, usize)>
+    where
+        K: Borrow<Q>,
+    {
+        let mut at = self.root;
+        while at != NIL {
+            let node = self.node(at);
+            match node.search(key) {
+                Ok(i) => return Some((at, i)),
+                Err(i) => at = node.children[i],
+            }
+        }
+        None
+    }
+
+    fn insert_in(&mut self, at: u32, key: K, value: V) -> Inserted<K, V> {
+        if at == NIL {
+            return Inserted::Split((key, value), NIL);
+        }
+        let node = self.node_mut(at);
+        let i = match node.search(&key) {
+            Ok(i) => {
+                let replaced = std::mem::replace(&mut node.values_mut()[i], value);
+                return Inserted::Replaced(replaced);
+            }
+            Err(i) => i,
+        };
+        let child = node.children[i];
+        match self.insert_in(child, key, value) {
+            Inserted::Split(item, rhs) => self.absorb(at, i, item, rhs),
+            inserted => inserted,
+        }
+    }
+
+    // As `Node::absorb`, with the upper half of a full node moved to a new
+    // slot.
+    fn absorb(&mut self, at: u32, i: usize, item: Item<K, V>, rhs: u32) -> Inserted<K, V> {
+        let node = self.node_mut(at);
+        if node.len() < 3 {
+            node.insert(i, item, i + 1, rhs);
+            return Inserted::Added;
+        }
+        let (item4, child4) = match i {
+            3 => (item, rhs),
+            _ => {
+                let last = node.remove(2, 3);
+                node.insert(i, item, i + 1, rhs);
+                last
+            }
+        };
+        let (item3, child3) = node.remove(2, 3);
+        let (item2, child2) = node.remove(1, 2);
+        let mut upper = Node::two(item3, child2, child3);
+        upper.insert(1, item4, 2, child4);
+        Inserted::Split(item2, self.alloc(upper))
+    }
+
+    // Remove the key from beneath this node, returning the item and whether
+    // the node was left as a hole.
+    fn remove_in<Q: Ord + ?Sized>(&mut self, at: u32, key: &Q) -> (Option<Item<K, V>>, bool)
+    where
+        K: Borrow<Q>,
+    {
+        if at == NIL {
+            return (None, false);
+        }
+        let node = self.node(at);
+        let i = match node.search(key) {
+            Ok(i) => {
+                let (item, reduced) = self.remove_at(at, i);
+                return (Some(item), reduced);
+            }
+            Err(i) => i,
+        };
+        let (result, reduced) = self.remove_in(node.children[i], key);
+        (result, reduced && self.fix(at, i))
+    }
+
+    fn remove_first_in(&mut self, at: u32) -> (Item<K, V>, bool) {
+        let node = self.node(at);
+        if node.is_leaf() {
+            return self.remove_at(at, 0);
+        }
+        let (item, reduced) = self.remove_first_in(node.children[0]);
+        (item, reduced && self.fix(at, 0))
+    }
+
+    fn remove_last_in(&mut self, at: u32) -> (Item<K, V>, bool) {
+        let node = self.node(at);
+        let n = node.len();
+        if node.is_leaf() {
+            return self.remove_at(at, n - 1);
+        }
+        let (item, reduced) = self.remove_last_in(node.children[n]);
+        (item, reduced && self.fix(at, n))
+    }
+
+    // Remove item `i` from this node. An internal item is replaced by its
+    // successor, which is always found in a leaf.
+    fn remove_at(&mut self, at: u32, i: usize) -> (Item<K, V>, bool) {
+        let node = self.node_mut(at);
+        if node.is_leaf() {
+            let (item, _nil) = node.remove(i, i + 1);
+            return (item, node.len() == 0);
+        }
+        let successor = node.children[i + 1];
+        let (small, reduced) = self.remove_first_in(successor);
+        let item = self.node_mut(at).replace(i, small);
+        (item, reduced && self.fix(at, i + 1))
+    }
+
+    // As `Node::fix`, for a child that is a hole.
+    fn fix(&mut self, at: u32, i: usize) -> bool {
+        let node = self.node(at);
+        let (s, sep) = if i < node.len() {
+            (i + 1, i)
+        } else {
+            (i - 1, i - 1)
+        };
+        let hole = node.children[i];
+        let sibling = node.children[s];
+        let orphan = self.node(hole).children[0];
+        let spare = self.node_mut(sibling);
+        if spare.len() > 1 {
+            let (item, grandchild) = if s > i {
+                spare.remove(0, 0)
+            } else {
+                spare.remove(spare.len() - 1, spare.len())
+            };
+            let separator = self.node_mut(at).replace(sep, item);
+            *self.node_mut(hole) = if s > i {
+                Node::two(separator, orphan, grandchild)
+            } else {
+                Node::two(separator, grandchild, orphan)
+            };
+            return false;
+        }
+        let (separator, _hole) = self.node_mut(at).remove(sep, i);
+        self.release(hole);
+        let merged = self.node_mut(sibling);
+        if s > i {
+            merged.insert(0, separator, 0, orphan);
+        } else {
+            merged.insert(1, separator, 2, orphan);
+        }
+        self.node(at).len() == 0
+    }
+
+    fn settle_root(&mut self, reduced: bool) {
+        if reduced {
+            let hole = self.root;
+            self.root = self.node(hole).children[0];
+            self.release(hole);
+        }
+    }
+}
+
+impl<K: Eq + Ord, V> Default for Tree234Arena<K, V> {
+    fn default() -> Tree234Arena<K, V> {
+        Tree234Arena::new()
+    }
+}
+
+impl<K: Eq + Ord + std::fmt::Debug, V: std::fmt::Debug> std::fmt::Debug for Tree234Arena<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Eq + Ord, V> FromIterator<Item<K, V>> for Tree234Arena<K, V> {
+    fn from_iter<I: IntoIterator<Item = Item<K, V>>>(iter: I) -> Tree234Arena<K, V> {
+        let mut tree = Tree234Arena::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<K: Eq + Ord, V> Extend<Item<K, V>> for Tree234Arena<K, V> {
+    fn extend<I: IntoIterator<Item = Item<K, V>>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K: Eq + Ord, V> IntoIterator for &'a Tree234Arena<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+// An end of the walk, as in `Tree234Iterator`, holding slot indices that
+// are looked up in the tree at each step.
+struct Edge {
+    nodes: [u32; MAX_DEPTH],
+    positions: [u8; MAX_DEPTH],
+    depth: usize,
+}
+
+impl Edge {
+    fn new() -> Edge {
+        Edge {
+            nodes: [NIL; MAX_DEPTH],
+            positions: [0; MAX_DEPTH],
+            depth: 0,
+        }
+    }
+
+    fn push(&mut self, at: u32, position: usize) {
+        self.nodes[self.depth] = at;
+        self.positions[self.depth] = position as u8;
+        self.depth += 1;
+    }
+
+    fn push_first<K: Eq + Ord, V>(&mut self, tree: &Tree234Arena<K, V>, mut at: u32) {
+        while at != NIL {
+            self.push(at, 0);
+            at = tree.node(at).children[0];
+        }
+    }
+
+    fn push_last<K: Eq + Ord, V>(&mut self, tree: &Tree234Arena<K, V>, mut at: u32) {
+        while at != NIL {
+            let node = tree.node(at);
+            self.push(at, node.len());
+            at = node.children[node.len()];
+        }
+    }
+
+    fn next_front<'a, K: Eq + Ord, V>(
+        &mut self,
+        tree: &'a Tree234Arena<K, V>,
+    ) -> Option<(&'a K, &'a V)> {
+        while self.depth > 0 {
+            let top = self.depth - 1;
+            let node = tree.node(self.nodes[top]);
+            let i = self.positions[top] as usize;
+            if i < node.len() {
+                self.positions[top] += 1;
+                self.push_first(tree, node.children[i + 1]);
+                return Some(node.entry(i));
+            }
+            self.depth -= 1;
+        }
+        None
+    }
+
+    fn next_back<'a, K: Eq + Ord, V>(
+        &mut self,
+        tree: &'a Tree234Arena<K, V>,
+    ) -> Option<(&'a K, &'a V)> {
+        while self.depth > 0 {
+            let top = self.depth - 1;
+            let node = tree.node(self.nodes[top]);
+            let j = self.positions[top] as usize;
+            if j > 0 {
+                self.positions[top] -= 1;
+                self.push_last(tree, node.children[j - 1]);
+                return Some(node.entry(j - 1));
+            }
+            self.depth -= 1;
+        }
+        None
+    }
+}
+
+pub struct Iter<'a, K: Eq + Ord, V> {
+    tree: &'a Tree234Arena<K, V>,
+    front: Edge,
+    back: Edge,
+    remaining: usize,
+}
+
+impl<'a, K: Eq + Ord, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.front.next_front(self.tree)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K: Eq + Ord, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.back.next_back(self.tree)
+    }
+}
+
+impl<K: Eq + Ord, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    // Check heights and key order below `at`, returning the height and the
+    // number of nodes.
+    fn check_node<K: Eq + Ord, V>(tree: &Tree234Arena<K, V>, at: u32) -> (usize, usize) {
+        if at == NIL {
+            return (0, 0);
+        }
+        let node = tree.node(at);
+        assert!((1..=3).contains(&node.len()));
+        for i in 1..node.len() {
+            assert!(node.keys()[i - 1] < node.keys()[i]);
+        }
+        let (height, mut count) = check_node(tree, node.children[0]);
+        for i in 1..=node.len() {
+            let (h, c) = check_node(tree, node.children[i]);
+            assert_eq!(h, height);
+            count += c;
+        }
+        (height + 1, count + 1)
+    }
+
+    fn check_tree<K: Eq + Ord, V>(tree: &Tree234Arena<K, V>) {
+        let (_height, count) = check_node(tree, tree.root);
+        let mut vacant = 0;
+        let mut at = tree.free;
+        while at != NIL {
+            vacant += 1;
+            match tree.slots[at as usize] {
+                Slot::Vacant(next) => at = next,
+                Slot::Occupied(_) => panic!("occupied slot on the free list"),
+            }
+        }
+        assert_eq!(count + vacant, tree.slots.len());
+    }
+
+    #[test]
+    fn arena_1() {
+        let mut rng = StdRng::seed_from_u64(71u64);
+        let mut tree = Tree234Arena::new();
+        let mut model = std::collections::BTreeMap::new();
+        for i in 0..20000u32 {
+            let k = rng.gen::<u32>() & 0x3ff;
+            match rng.gen::<u32>() % 8 {
+                0 => assert_eq!(tree.pop_first(), model.pop_first()),
+                1 => assert_eq!(tree.pop_last(), model.pop_last()),
+                2..=4 => assert_eq!(tree.remove(&k), model.remove(&k)),
+                _ => assert_eq!(tree.insert(k, i), model.insert(k, i)),
+            }
+            assert_eq!(tree.size(), model.len());
+            if i % 97 == 0 {
+                check_tree(&tree);
+                assert!(tree.iter().eq(model.iter()));
+            }
+        }
+        assert_eq!(tree.first(), model.first_key_value());
+        assert_eq!(tree.last(), model.last_key_value());
+        // Alternate ends until the two walks meet.
+        let mut iter = tree.iter();
+        let mut expect = model.iter();
+        loop {
+            let (got, want) = if rng.gen::<bool>() {
+                (iter.next(), expect.next())
+            } else {
+                (iter.next_back(), expect.next_back())
+            };
+            assert_eq!(got, want);
+            assert_eq!(iter.len(), expect.len());
+            if got.is_none() {
+                break;
+            }
+        }
+        // Churn reuses freed slots rather than growing the slab.
+        assert!(tree.slots.len() < 2 * 0x400);
+        tree.clear();
+        assert_eq!(tree.size(), 0);
+        assert!(tree.iter().next().is_none());
+    }
+
+    #[test]
+    fn arena_2() {
+        let mut tree: Tree234Arena<String, usize> =
+            (0..100).map(|i| (format!("{:03}", i), i)).collect();
+        assert_eq!(tree.get("042"), Some(&42));
+        assert_eq!(tree.get_key_value("042"), Some((&"042".to_string(), &42)));
+        *tree.get_mut("042").unwrap() += 1000;
+        assert_eq!(tree.remove("042"), Some(1042));
+        assert!(!tree.contains_key("042"));
+        assert_eq!(tree.iter().len(), 99);
+        assert!(tree
+            .iter()
+            .rev()
+            .eq(tree.iter().collect::<Vec<_>>().into_iter().rev()));
+        check_tree(&tree);
+        let copy = tree.clone();
+        check_tree(&copy);
+        assert!(copy.iter().eq(tree.iter()));
+        tree.clear();
+        assert_eq!(tree.size(), 0);
+        assert!(tree.iter().next().is_none());
+        tree.insert("x".to_string(), 1);
+        assert_eq!(format!("{:?}", tree), "{\"x\": 1}");
+    }
+}
@@ -1,5 +1,24 @@
-mod tree234;
+pub mod arena;
 pub mod either;
+pub mod set;
+mod tree234;
 
+pub use arena::Tree234Arena;
+pub use set::Tree234Set;
+pub use tree234::Augment;
+pub use tree234::BuildError;
+pub use tree234::Cursor;
+pub use tree234::CursorMut;
+pub use tree234::Drain;
+pub use tree234::Entry;
+pub use tree234::ExtractIf;
+pub use tree234::IntoIter;
+pub use tree234::IterMut;
+pub use tree234::Keys;
+pub use tree234::OccupiedEntry;
+pub use tree234::Range;
 pub use tree234::Tree234;
-pub use tree234::Tree234Iterator;
\ No newline at end of file
+pub use tree234::Tree234Iterator;
+pub use tree234::VacantEntry;
+pub use tree234::Values;
+pub use tree234::ValuesMut;
@@ -0,0 +1,458 @@
+use std::borrow::Borrow;
+use std::iter::Peekable;
+use std::ops::RangeBounds;
+
+use crate::tree234::Tree234;
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Tree234Set<K: Eq + Ord> {
+    map: Tree234<K, ()>,
+}
+
+impl<K: Eq + Ord> Tree234Set<K> {
+    pub fn new() -> Tree234Set<K> {
+        Tree234Set {
+            map: Tree234::new(),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.map.size()
+    }
+
+    pub fn insert(&mut self, key: K) -> bool {
+        self.map.insert(key, ()).is_none()
+    }
+
+    // Unlike `insert`, an equal key already in the set is swapped out and returned.
+    pub fn replace(&mut self, key: K) -> Option<K> {
+        let replaced = self.map.remove_entry(&key).map(|item| item.0);
+        self.map.insert(key, ());
+        replaced
+    }
+
+    pub fn contains<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.map.contains_key(key)
+    }
+
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+    {
+        self.map.get(key).map(|item| &item.0)
+    }
+
+    pub fn remove<Q: Ord + ?Sized>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.map.remove_entry(key).is_some()
+    }
+
+    pub fn take<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<K>
+    where
+        K: Borrow<Q>,
+    {
+        self.map.remove_entry(key).map(|item| item.0)
+    }
+
+    pub fn nth(&self, index: usize) -> Option<&K> {
+        self.map.nth(index).map(|item| &item.0)
+    }
+
+    pub fn rank<Q: Ord + ?Sized>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        self.map.rank(key)
+    }
+
+    pub fn first(&self) -> Option<&K> {
+        self.map.first().map(|item| &item.0)
+    }
+
+    pub fn last(&self) -> Option<&K> {
+        self.map.last().map(|item| &item.0)
+    }
+
+    pub fn pop_first(&mut self) -> Option<K> {
+        self.map.pop_first().map(|item| item.0)
+    }
+
+    pub fn pop_last(&mut self) -> Option<K> {
+        self.map.pop_last().map(|item| item.0)
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    pub fn iter(&self) -> Iter<'_, K> {
+        Iter {
+            inner: self.map.keys(),
+        }
+    }
+
+    pub fn range<Q: Ord + ?Sized, R: RangeBounds<Q>>(&self, range: R) -> Range<'_, K>
+    where
+        K: Borrow<Q>,
+    {
+        Range {
+            inner: self.map.range(range),
+        }
+    }
+
+    pub fn union<'a>(&'a self, other: &'a Tree234Set<K>) -> Union<'a, K> {
+        Union {
+            lhs: self.iter().peekable(),
+            rhs: other.iter().peekable(),
+        }
+    }
+
+    pub fn intersection<'a>(&'a self, other: &'a Tree234Set<K>) -> Intersection<'a, K> {
+        Intersection {
+            lhs: self.iter().peekable(),
+            rhs: other.iter().peekable(),
+        }
+    }
+
+    pub fn difference<'a>(&'a self, other: &'a Tree234Set<K>) -> Difference<'a, K> {
+        Difference {
+            lhs: self.iter().peekable(),
+            rhs: other.iter().peekable(),
+        }
+    }
+
+    pub fn symmetric_difference<'a>(
+        &'a self,
+        other: &'a Tree234Set<K>,
+    ) -> SymmetricDifference<'a, K> {
+        SymmetricDifference {
+            lhs: self.iter().peekable(),
+            rhs: other.iter().peekable(),
+        }
+    }
+
+    pub fn is_subset(&self, other: &Tree234Set<K>) -> bool {
+        self.size() <= other.size() && self.difference(other).next().is_none()
+    }
+
+    pub fn is_superset(&self, other: &Tree234Set<K>) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &Tree234Set<K>) -> bool {
+        self.intersection(other).next().is_none()
+    }
+}
+
+impl<K: Eq + Ord> Default for Tree234Set<K> {
+    fn default() -> Tree234Set<K> {
+        Tree234Set::new()
+    }
+}
+
+impl<K: Eq + Ord + std::fmt::Debug> std::fmt::Debug for Tree234Set<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Eq + Ord> FromIterator<K> for Tree234Set<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Tree234Set<K> {
+        Tree234Set {
+            map: iter.into_iter().map(|key| (key, ())).collect(),
+        }
+    }
+}
+
+impl<K: Eq + Ord> Extend<K> for Tree234Set<K> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        self.map.extend(iter.into_iter().map(|key| (key, ())));
+    }
+}
+
+impl<K: Eq + Ord, const N: usize> From<[K; N]> for Tree234Set<K> {
+    fn from(keys: [K; N]) -> Tree234Set<K> {
+        Tree234Set::from_iter(keys)
+    }
+}
+
+impl<K: Eq + Ord> IntoIterator for Tree234Set<K> {
+    type Item = K;
+    type IntoIter = IntoIter<K>;
+
+    fn into_iter(self) -> IntoIter<K> {
+        IntoIter {
+            inner: self.map.into_iter(),
+        }
+    }
+}
+
+impl<'a, K: Eq + Ord> IntoIterator for &'a Tree234Set<K> {
+    type Item = &'a K;
+    type IntoIter = Iter<'a, K>;
+
+    fn into_iter(self) -> Iter<'a, K> {
+        self.iter()
+    }
+}
+
+pub struct Iter<'a, K: Eq + Ord> {
+    inner: crate::tree234::Keys<'a, K, ()>,
+}
+
+impl<'a, K: Eq + Ord> Iterator for Iter<'a, K> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K: Eq + Ord> DoubleEndedIterator for Iter<'_, K> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<K: Eq + Ord> ExactSizeIterator for Iter<'_, K> {}
+
+pub struct Range<'a, K: Eq + Ord> {
+    inner: crate::tree234::Range<'a, K, ()>,
+}
+
+impl<'a, K: Eq + Ord> Iterator for Range<'a, K> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|item| &item.0)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K: Eq + Ord> DoubleEndedIterator for Range<'_, K> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|item| &item.0)
+    }
+}
+
+impl<K: Eq + Ord> ExactSizeIterator for Range<'_, K> {}
+
+pub struct IntoIter<K: Eq + Ord> {
+    inner: crate::tree234::IntoIter<K, ()>,
+}
+
+impl<K: Eq + Ord> Iterator for IntoIter<K> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|item| item.0)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K: Eq + Ord> DoubleEndedIterator for IntoIter<K> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|item| item.0)
+    }
+}
+
+impl<K: Eq + Ord> ExactSizeIterator for IntoIter<K> {}
+
+pub struct Union<'a, K: Eq + Ord> {
+    lhs: Peekable<Iter<'a, K>>,
+    rhs: Peekable<Iter<'a, K>>,
+}
+
+impl<'a, K: Eq + Ord> Iterator for Union<'a, K> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match (self.lhs.peek(), self.rhs.peek()) {
+            (Some(a), Some(b)) => match a.cmp(b) {
+                std::cmp::Ordering::Less => self.lhs.next(),
+                std::cmp::Ordering::Equal => {
+                    self.rhs.next();
+                    self.lhs.next()
+                }
+                std::cmp::Ordering::Greater => self.rhs.next(),
+            },
+            (Some(_), None) => self.lhs.next(),
+            (None, _) => self.rhs.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (a, b) = (self.lhs.len(), self.rhs.len());
+        (a.max(b), a.checked_add(b))
+    }
+}
+
+pub struct Intersection<'a, K: Eq + Ord> {
+    lhs: Peekable<Iter<'a, K>>,
+    rhs: Peekable<Iter<'a, K>>,
+}
+
+impl<'a, K: Eq + Ord> Iterator for Intersection<'a, K> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let a = *self.lhs.peek()?;
+            let b = *self.rhs.peek()?;
+            match a.cmp(b) {
+                std::cmp::Ordering::Less => {
+                    self.lhs.next();
+                }
+                std::cmp::Ordering::Equal => {
+                    self.rhs.next();
+                    return self.lhs.next();
+                }
+                std::cmp::Ordering::Greater => {
+                    self.rhs.next();
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (a, b) = (self.lhs.len(), self.rhs.len());
+        (0, Some(a.min(b)))
+    }
+}
+
+pub struct Difference<'a, K: Eq + Ord> {
+    lhs: Peekable<Iter<'a, K>>,
+    rhs: Peekable<Iter<'a, K>>,
+}
+
+impl<'a, K: Eq + Ord> Iterator for Difference<'a, K> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let a = *self.lhs.peek()?;
+            let Some(b) = self.rhs.peek() else {
+                return self.lhs.next();
+            };
+            match a.cmp(b) {
+                std::cmp::Ordering::Less => return self.lhs.next(),
+                std::cmp::Ordering::Equal => {
+                    self.lhs.next();
+                    self.rhs.next();
+                }
+                std::cmp::Ordering::Greater => {
+                    self.rhs.next();
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (a, b) = (self.lhs.len(), self.rhs.len());
+        (a.saturating_sub(b), Some(a))
+    }
+}
+
+pub struct SymmetricDifference<'a, K: Eq + Ord> {
+    lhs: Peekable<Iter<'a, K>>,
+    rhs: Peekable<Iter<'a, K>>,
+}
+
+impl<'a, K: Eq + Ord> Iterator for SymmetricDifference<'a, K> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match (self.lhs.peek(), self.rhs.peek()) {
+                (Some(a), Some(b)) => match a.cmp(b) {
+                    std::cmp::Ordering::Less => return self.lhs.next(),
+                    std::cmp::Ordering::Equal => {
+                        self.lhs.next();
+                        self.rhs.next();
+                    }
+                    std::cmp::Ordering::Greater => return self.rhs.next(),
+                },
+                (Some(_), None) => return self.lhs.next(),
+                (None, _) => return self.rhs.next(),
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (a, b) = (self.lhs.len(), self.rhs.len());
+        (a.abs_diff(b), a.checked_add(b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_1() {
+        let mut set = Tree234Set::new();
+        assert!(set.insert(5));
+        assert!(set.insert(1));
+        assert!(!set.insert(5));
+        assert!(set.insert(9));
+        assert_eq!(set.size(), 3);
+        assert!(set.contains(&1));
+        assert!(!set.contains(&2));
+        assert_eq!(set.nth(1), Some(&5));
+        assert_eq!(set.rank(&6), 2);
+        assert_eq!(set.range(2..).cloned().collect::<Vec<u32>>(), vec![5, 9]);
+        assert_eq!(set.replace(5), Some(5));
+        assert_eq!(set.replace(7), None);
+        assert_eq!(set.take(&1), Some(1));
+        assert!(!set.remove(&1));
+        assert!(set.remove(&9));
+        assert_eq!(set.iter().rev().cloned().collect::<Vec<u32>>(), vec![7, 5]);
+        assert_eq!(format!("{:?}", set), "{5, 7}");
+        assert_eq!(set.into_iter().collect::<Vec<u32>>(), vec![5, 7]);
+    }
+
+    #[test]
+    fn set_algebra_1() {
+        let lhs = (0..30).filter(|x| x % 2 == 0).collect::<Tree234Set<u32>>();
+        let rhs = (0..30).filter(|x| x % 3 == 0).collect::<Tree234Set<u32>>();
+        let union = lhs.union(&rhs).cloned().collect::<Vec<u32>>();
+        let intersection = lhs.intersection(&rhs).cloned().collect::<Vec<u32>>();
+        let difference = lhs.difference(&rhs).cloned().collect::<Vec<u32>>();
+        let symmetric = lhs
+            .symmetric_difference(&rhs)
+            .cloned()
+            .collect::<Vec<u32>>();
+        let expect = |f: &dyn Fn(u32) -> bool| (0..30).filter(|x| f(*x)).collect::<Vec<u32>>();
+        assert_eq!(union, expect(&|x| x % 2 == 0 || x % 3 == 0));
+        assert_eq!(intersection, expect(&|x| x % 6 == 0));
+        assert_eq!(difference, expect(&|x| x % 2 == 0 && x % 3 != 0));
+        assert_eq!(symmetric, expect(&|x| (x % 2 == 0) != (x % 3 == 0)));
+        assert_eq!(lhs.union(&rhs).size_hint(), (15, Some(25)));
+        assert_eq!(lhs.intersection(&rhs).size_hint(), (0, Some(10)));
+        assert_eq!(lhs.difference(&rhs).size_hint(), (5, Some(15)));
+        assert_eq!(rhs.difference(&lhs).size_hint(), (0, Some(10)));
+        assert_eq!(lhs.symmetric_difference(&rhs).size_hint(), (5, Some(25)));
+        let sixes = (0..30).filter(|x| x % 6 == 0).collect::<Tree234Set<u32>>();
+        assert!(sixes.is_subset(&lhs));
+        assert!(rhs.is_superset(&sixes));
+        assert!(!lhs.is_subset(&rhs));
+        assert!(!lhs.is_disjoint(&rhs));
+        assert!(Tree234Set::from([1, 3]).is_disjoint(&Tree234Set::from([0, 2, 4])));
+        assert!(Tree234Set::<u32>::new().is_subset(&lhs));
+    }
+}
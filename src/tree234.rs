@@ -1,61 +1,116 @@
+use std::borrow::Borrow;
 use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
 
 use crate::either::Either;
 
 pub type Item<K, V> = (K, V);
 
-enum Node<K: Eq + Ord, V> {
+// A summary of a run of items, kept for every subtree. `combine` must be
+// associative with `identity` as its unit, but need not be commutative:
+// summaries are always combined in key order.
+pub trait Augment<K, V>: Clone {
+    fn identity() -> Self;
+    fn from_item(key: &K, value: &V) -> Self;
+    fn combine(&self, other: &Self) -> Self;
+}
+
+impl<K, V> Augment<K, V> for () {
+    fn identity() {}
+
+    fn from_item(_key: &K, _value: &V) {}
+
+    fn combine(&self, _other: &()) {}
+}
+
+#[derive(Clone)]
+enum Node<K: Eq + Ord, V, A: Augment<K, V> = ()> {
     Empty,
-    Two(Two<K, V>),
-    Three(Three<K, V>),
-    Four(Four<K, V>),
+    Two(Two<K, V, A>),
+    Three(Three<K, V, A>),
+    Four(Four<K, V, A>),
 }
 
-impl<K: Eq + Ord, V> Node<K, V> {
-    fn empty() -> NodeBox<K, V> {
-        Box::new(Node::Empty)
+impl<K: Eq + Ord, V, A: Augment<K, V>> Node<K, V, A> {
+    fn empty() -> NodeBox<K, V, A> {
+        NodeBox(None)
+    }
+
+    fn two(item: Item<K, V>, lhs: NodeBox<K, V, A>, rhs: NodeBox<K, V, A>) -> NodeBox<K, V, A> {
+        NodeBox(Some(Box::new(Node::new2(item, lhs, rhs))))
+    }
+
+    fn three(
+        item1: Item<K, V>,
+        item2: Item<K, V>,
+        lhs: NodeBox<K, V, A>,
+        mid: NodeBox<K, V, A>,
+        rhs: NodeBox<K, V, A>,
+    ) -> NodeBox<K, V, A> {
+        NodeBox(Some(Box::new(Node::new3(item1, item2, lhs, mid, rhs))))
+    }
+
+    fn four(
+        item1: Item<K, V>,
+        item2: Item<K, V>,
+        item3: Item<K, V>,
+        lhs: NodeBox<K, V, A>,
+        lhs_mid: NodeBox<K, V, A>,
+        rhs_mid: NodeBox<K, V, A>,
+        rhs: NodeBox<K, V, A>,
+    ) -> NodeBox<K, V, A> {
+        NodeBox(Some(Box::new(Node::new4(
+            item1, item2, item3, lhs, lhs_mid, rhs_mid, rhs,
+        ))))
     }
 
-    fn two(item: Item<K, V>, lhs: NodeBox<K, V>, rhs: NodeBox<K, V>) -> NodeBox<K, V> {
+    fn new2(item: Item<K, V>, lhs: NodeBox<K, V, A>, rhs: NodeBox<K, V, A>) -> Node<K, V, A> {
         let size = 1 + lhs.size() + rhs.size();
-        Box::new(Node::Two(Two {
+        let summary = Node::summarise(&[&item], &[&lhs, &rhs]);
+        Node::Two(Two {
             size,
+            summary,
             item,
             lhs,
             rhs,
-        }))
+        })
     }
 
-    fn three(
+    fn new3(
         item1: Item<K, V>,
         item2: Item<K, V>,
-        lhs: NodeBox<K, V>,
-        mid: NodeBox<K, V>,
-        rhs: NodeBox<K, V>,
-    ) -> NodeBox<K, V> {
+        lhs: NodeBox<K, V, A>,
+        mid: NodeBox<K, V, A>,
+        rhs: NodeBox<K, V, A>,
+    ) -> Node<K, V, A> {
         let size = 2 + lhs.size() + mid.size() + rhs.size();
-        Box::new(Node::Three(Three {
+        let summary = Node::summarise(&[&item1, &item2], &[&lhs, &mid, &rhs]);
+        Node::Three(Three {
             size,
+            summary,
             item1,
             item2,
             lhs,
             mid,
             rhs,
-        }))
+        })
     }
 
-    fn four(
+    fn new4(
         item1: Item<K, V>,
         item2: Item<K, V>,
         item3: Item<K, V>,
-        lhs: NodeBox<K, V>,
-        lhs_mid: NodeBox<K, V>,
-        rhs_mid: NodeBox<K, V>,
-        rhs: NodeBox<K, V>,
-    ) -> NodeBox<K, V> {
+        lhs: NodeBox<K, V, A>,
+        lhs_mid: NodeBox<K, V, A>,
+        rhs_mid: NodeBox<K, V, A>,
+        rhs: NodeBox<K, V, A>,
+    ) -> Node<K, V, A> {
         let size = 3 + lhs.size() + lhs_mid.size() + rhs_mid.size() + rhs.size();
-        Box::new(Node::Four(Four {
+        let summary = Node::summarise(&[&item1, &item2, &item3], &[&lhs, &lhs_mid, &rhs_mid, &rhs]);
+        Node::Four(Four {
             size,
+            summary,
             item1,
             item2,
             item3,
@@ -63,13 +118,117 @@ impl<K: Eq + Ord, V> Node<K, V> {
             lhs_mid,
             rhs_mid,
             rhs,
-        }))
+        })
+    }
+
+    // Recompute the size and summary after a change beneath this node.
+    fn refresh(&mut self) {
+        match self {
+            Node::Empty => {}
+            Node::Two(two) => {
+                two.size = 1 + two.lhs.size() + two.rhs.size();
+                two.summary = Node::summarise(&[&two.item], &[&two.lhs, &two.rhs]);
+            }
+            Node::Three(three) => {
+                three.size = 2 + three.lhs.size() + three.mid.size() + three.rhs.size();
+                three.summary = Node::summarise(
+                    &[&three.item1, &three.item2],
+                    &[&three.lhs, &three.mid, &three.rhs],
+                );
+            }
+            Node::Four(four) => {
+                four.size = 3
+                    + four.lhs.size()
+                    + four.lhs_mid.size()
+                    + four.rhs_mid.size()
+                    + four.rhs.size();
+                four.summary = Node::summarise(
+                    &[&four.item1, &four.item2, &four.item3],
+                    &[&four.lhs, &four.lhs_mid, &four.rhs_mid, &four.rhs],
+                );
+            }
+        }
+    }
+
+    // Combine the summaries of children and items in key order.
+    fn summarise(node_items: &[&Item<K, V>], children: &[&NodeBox<K, V, A>]) -> A {
+        let mut summary = A::identity();
+        for (i, child) in children.iter().enumerate() {
+            if let Some(child) = child.summary() {
+                summary = summary.combine(child);
+            }
+            if let Some(item) = node_items.get(i) {
+                summary = summary.combine(&A::from_item(&item.0, &item.1));
+            }
+        }
+        summary
     }
 
     fn is_empty(&self) -> bool {
+        matches!(self, Node::Empty)
+    }
+
+    // The number of items held directly in this node.
+    fn len(&self) -> usize {
         match self {
-            Node::Empty => true,
-            _ => false,
+            Node::Empty => 0,
+            Node::Two(_) => 1,
+            Node::Three(_) => 2,
+            Node::Four(_) => 3,
+        }
+    }
+
+    fn item(&self, i: usize) -> &Item<K, V> {
+        match (self, i) {
+            (Node::Two(two), 0) => &two.item,
+            (Node::Three(three), 0) => &three.item1,
+            (Node::Three(three), 1) => &three.item2,
+            (Node::Four(four), 0) => &four.item1,
+            (Node::Four(four), 1) => &four.item2,
+            (Node::Four(four), 2) => &four.item3,
+            _ => panic!("item index out of range"),
+        }
+    }
+
+    fn child(&self, i: usize) -> &NodeBox<K, V, A> {
+        match (self, i) {
+            (Node::Two(two), 0) => &two.lhs,
+            (Node::Two(two), 1) => &two.rhs,
+            (Node::Three(three), 0) => &three.lhs,
+            (Node::Three(three), 1) => &three.mid,
+            (Node::Three(three), 2) => &three.rhs,
+            (Node::Four(four), 0) => &four.lhs,
+            (Node::Four(four), 1) => &four.lhs_mid,
+            (Node::Four(four), 2) => &four.rhs_mid,
+            (Node::Four(four), 3) => &four.rhs,
+            _ => panic!("child index out of range"),
+        }
+    }
+
+    fn item_mut(&mut self, i: usize) -> &mut Item<K, V> {
+        match (self, i) {
+            (Node::Two(two), 0) => &mut two.item,
+            (Node::Three(three), 0) => &mut three.item1,
+            (Node::Three(three), 1) => &mut three.item2,
+            (Node::Four(four), 0) => &mut four.item1,
+            (Node::Four(four), 1) => &mut four.item2,
+            (Node::Four(four), 2) => &mut four.item3,
+            _ => panic!("item index out of range"),
+        }
+    }
+
+    fn child_mut(&mut self, i: usize) -> &mut NodeBox<K, V, A> {
+        match (self, i) {
+            (Node::Two(two), 0) => &mut two.lhs,
+            (Node::Two(two), 1) => &mut two.rhs,
+            (Node::Three(three), 0) => &mut three.lhs,
+            (Node::Three(three), 1) => &mut three.mid,
+            (Node::Three(three), 2) => &mut three.rhs,
+            (Node::Four(four), 0) => &mut four.lhs,
+            (Node::Four(four), 1) => &mut four.lhs_mid,
+            (Node::Four(four), 2) => &mut four.rhs_mid,
+            (Node::Four(four), 3) => &mut four.rhs,
+            _ => panic!("child index out of range"),
         }
     }
 
@@ -82,849 +241,948 @@ impl<K: Eq + Ord, V> Node<K, V> {
         }
     }
 
-    pub fn get(&self, key: &K) -> Option<&Item<K, V>> {
+    fn summary(&self) -> Option<&A> {
         match self {
             Node::Empty => None,
-            Node::Two(two) => match key.cmp(&two.item.0) {
-                std::cmp::Ordering::Less => two.lhs.get(key),
-                std::cmp::Ordering::Equal => Some(&two.item),
-                std::cmp::Ordering::Greater => two.rhs.get(key),
-            },
-            Node::Three(three) => match key.cmp(&three.item1.0) {
-                std::cmp::Ordering::Less => three.lhs.get(key),
-                std::cmp::Ordering::Equal => Some(&three.item1),
-                std::cmp::Ordering::Greater => match key.cmp(&three.item2.0) {
-                    std::cmp::Ordering::Less => three.mid.get(key),
-                    std::cmp::Ordering::Equal => Some(&three.item2),
-                    std::cmp::Ordering::Greater => three.rhs.get(key),
-                },
-            },
-            Node::Four(four) => match key.cmp(&four.item1.0) {
-                std::cmp::Ordering::Less => four.lhs.get(key),
-                std::cmp::Ordering::Equal => Some(&four.item1),
-                std::cmp::Ordering::Greater => match key.cmp(&four.item2.0) {
-                    std::cmp::Ordering::Less => four.lhs_mid.get(key),
-                    std::cmp::Ordering::Equal => Some(&four.item2),
-                    std::cmp::Ordering::Greater => match key.cmp(&four.item3.0) {
-                        std::cmp::Ordering::Less => four.rhs_mid.get(key),
-                        std::cmp::Ordering::Equal => Some(&four.item3),
-                        std::cmp::Ordering::Greater => four.rhs.get(key),
-                    },
-                },
-            },
+            Node::Two(two) => Some(&two.summary),
+            Node::Three(three) => Some(&three.summary),
+            Node::Four(four) => Some(&four.summary),
         }
     }
 
-    pub fn insert(self, key: K, value: V) -> (NodeBox<K, V>, Option<V>) {
-        match self {
-            Node::Empty => (Node::two((key, value), Node::empty(), Node::empty()), None),
-            Node::Two(two) => Node::insert2(two, key, value),
-            Node::Three(three) => Node::insert3(three, key, value),
-            Node::Four(four) => {
-                let (mid_item, mid_lhs, mid_rhs) = Node::split_four(four);
-                match key.cmp(&mid_item.0) {
-                    std::cmp::Ordering::Less => {
-                        let (mid_lhs, replaced) = mid_lhs.insert(key, value);
-                        (Node::two(mid_item, mid_lhs, mid_rhs), replaced)
-                    }
-                    std::cmp::Ordering::Equal => {
-                        let replaced = Some(mid_item.1);
-                        let mid_item = (mid_item.0, value);
-                        (Node::two(mid_item, mid_lhs, mid_rhs), replaced)
-                    }
-                    std::cmp::Ordering::Greater => {
-                        let (mid_rhs, replaced) = mid_rhs.insert(key, value);
-                        (Node::two(mid_item, mid_lhs, mid_rhs), replaced)
-                    }
+    // Combine the summaries of the items within both bounds. Once a side's
+    // bound is known to hold for a whole subtree it is dropped, so only the
+    // two paths to the bounds are walked.
+    fn aggregate<Q: Ord + ?Sized>(&self, lower: Bound<&Q>, upper: Bound<&Q>) -> A
+    where
+        K: Borrow<Q>,
+    {
+        if self.is_empty() {
+            return A::identity();
+        }
+        if let (Bound::Unbounded, Bound::Unbounded) = (lower, upper) {
+            return self.summary().cloned().unwrap_or_else(A::identity);
+        }
+        let n = self.len();
+        let mut summary = A::identity();
+        for i in 0..=n {
+            let prev = i.checked_sub(1).map(|j| &self.item(j).0);
+            let next = (i < n).then(|| &self.item(i).0);
+            let skip = next.is_some_and(|key| Self::below(key, lower))
+                || prev.is_some_and(|key| Self::above(key, upper));
+            if !skip {
+                let lower = match prev {
+                    Some(key) if !Self::below(key, lower) => Bound::Unbounded,
+                    _ => lower,
+                };
+                let upper = match next {
+                    Some(key) if !Self::above(key, upper) => Bound::Unbounded,
+                    _ => upper,
+                };
+                summary = summary.combine(&self.child(i).aggregate(lower, upper));
+            }
+            if let Some(key) = next {
+                if !Self::below(key, lower) && !Self::above(key, upper) {
+                    let item = self.item(i);
+                    summary = summary.combine(&A::from_item(&item.0, &item.1));
                 }
             }
         }
+        summary
     }
 
-    fn insert2(two: Two<K, V>, key: K, value: V) -> (NodeBox<K, V>, Option<V>) {
-        let Two {
-            size: _,
-            item,
-            lhs,
-            rhs,
-        } = two;
-        if lhs.is_empty() {
-            assert!(rhs.is_empty());
-            match key.cmp(&item.0) {
-                std::cmp::Ordering::Less => (
-                    Node::three((key, value), item, lhs, Node::empty(), rhs),
-                    None,
-                ),
-                std::cmp::Ordering::Equal => (Node::two((item.0, value), lhs, rhs), Some(item.1)),
-                std::cmp::Ordering::Greater => (
-                    Node::three(item, (key, value), lhs, Node::empty(), rhs),
-                    None,
-                ),
-            }
-        } else {
-            match key.cmp(&item.0) {
-                std::cmp::Ordering::Less => match *lhs {
-                    Node::Empty => {
-                        let lhs = Node::two((key, value), Node::empty(), Node::empty());
-                        (Node::two(item, lhs, rhs), None)
-                    }
-                    Node::Two(two) => {
-                        let (lhs, replaced) = Node::insert2(two, key, value);
-                        (Node::two(item, lhs, rhs), replaced)
-                    }
-                    Node::Three(three) => {
-                        let (lhs, replaced) = Node::insert3(three, key, value);
-                        (Node::two(item, lhs, rhs), replaced)
-                    }
-                    Node::Four(four) => {
-                        let (mid_item, mid_lhs, mid_rhs) = Node::split_four(four);
-                        match key.cmp(&mid_item.0) {
-                            std::cmp::Ordering::Less => {
-                                let (mid_lhs, replaced) = mid_lhs.insert(key, value);
-                                (Node::three(mid_item, item, mid_lhs, mid_rhs, rhs), replaced)
-                            }
-                            std::cmp::Ordering::Equal => {
-                                let replaced = Some(mid_item.1);
-                                let mid_item = (mid_item.0, value);
-                                (Node::three(mid_item, item, mid_lhs, mid_rhs, rhs), replaced)
-                            }
-                            std::cmp::Ordering::Greater => {
-                                let (mid_rhs, replaced) = mid_rhs.insert(key, value);
-                                (Node::three(mid_item, item, mid_lhs, mid_rhs, rhs), replaced)
-                            }
-                        }
-                    }
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&Item<K, V>>
+    where
+        K: Borrow<Q>,
+    {
+        let mut node = self;
+        loop {
+            node = match node {
+                Node::Empty => return None,
+                Node::Two(two) => match key.cmp(two.item.0.borrow()) {
+                    std::cmp::Ordering::Less => &two.lhs,
+                    std::cmp::Ordering::Equal => return Some(&two.item),
+                    std::cmp::Ordering::Greater => &two.rhs,
                 },
-                std::cmp::Ordering::Equal => {
-                    let replaced = Some(item.1);
-                    let item = (item.0, value);
-                    (Node::two(item, lhs, rhs), replaced)
-                }
-                std::cmp::Ordering::Greater => match *rhs {
-                    Node::Empty => {
-                        let rhs = Node::two((key, value), Node::empty(), Node::empty());
-                        (Node::two(item, lhs, rhs), None)
-                    }
-                    Node::Two(two) => {
-                        let (rhs, replaced) = Node::insert2(two, key, value);
-                        (Node::two(item, lhs, rhs), replaced)
-                    }
-                    Node::Three(three) => {
-                        let (rhs, replaced) = Node::insert3(three, key, value);
-                        (Node::two(item, lhs, rhs), replaced)
-                    }
-                    Node::Four(four) => {
-                        let (mid_item, mid_lhs, mid_rhs) = Node::split_four(four);
-                        match key.cmp(&mid_item.0) {
-                            std::cmp::Ordering::Less => {
-                                let (mid_lhs, replaced) = mid_lhs.insert(key, value);
-                                (Node::three(item, mid_item, lhs, mid_lhs, mid_rhs), replaced)
-                            }
-                            std::cmp::Ordering::Equal => {
-                                let replaced = Some(mid_item.1);
-                                let mid_item = (mid_item.0, value);
-                                (Node::three(item, mid_item, lhs, mid_lhs, mid_rhs), replaced)
-                            }
-                            std::cmp::Ordering::Greater => {
-                                let (mid_rhs, replaced) = mid_rhs.insert(key, value);
-                                (Node::three(item, mid_item, lhs, mid_lhs, mid_rhs), replaced)
-                            }
-                        }
-                    }
+                Node::Three(three) => match key.cmp(three.item1.0.borrow()) {
+                    std::cmp::Ordering::Less => &three.lhs,
+                    std::cmp::Ordering::Equal => return Some(&three.item1),
+                    std::cmp::Ordering::Greater => match key.cmp(three.item2.0.borrow()) {
+                        std::cmp::Ordering::Less => &three.mid,
+                        std::cmp::Ordering::Equal => return Some(&three.item2),
+                        std::cmp::Ordering::Greater => &three.rhs,
+                    },
                 },
-            }
+                Node::Four(four) => match key.cmp(four.item1.0.borrow()) {
+                    std::cmp::Ordering::Less => &four.lhs,
+                    std::cmp::Ordering::Equal => return Some(&four.item1),
+                    std::cmp::Ordering::Greater => match key.cmp(four.item2.0.borrow()) {
+                        std::cmp::Ordering::Less => &four.lhs_mid,
+                        std::cmp::Ordering::Equal => return Some(&four.item2),
+                        std::cmp::Ordering::Greater => match key.cmp(four.item3.0.borrow()) {
+                            std::cmp::Ordering::Less => &four.rhs_mid,
+                            std::cmp::Ordering::Equal => return Some(&four.item3),
+                            std::cmp::Ordering::Greater => &four.rhs,
+                        },
+                    },
+                },
+            };
         }
     }
 
-    fn insert3(three: Three<K, V>, key: K, value: V) -> (NodeBox<K, V>, Option<V>) {
-        let Three {
-            size: _,
-            item1,
-            item2,
-            lhs,
-            mid,
-            rhs,
-        } = three;
-        if lhs.is_empty() {
-            assert!(mid.is_empty());
-            assert!(rhs.is_empty());
-            match key.cmp(&item1.0) {
-                std::cmp::Ordering::Less => (
-                    Node::four((key, value), item1, item2, lhs, mid, Node::empty(), rhs),
-                    None,
-                ),
-                std::cmp::Ordering::Equal => (
-                    Node::three((item1.0, value), item2, lhs, mid, rhs),
-                    Some(item1.1),
-                ),
-                std::cmp::Ordering::Greater => match key.cmp(&item2.0) {
-                    std::cmp::Ordering::Less => (
-                        Node::four(item1, (key, value), item2, lhs, mid, Node::empty(), rhs),
-                        None,
-                    ),
-                    std::cmp::Ordering::Equal => (
-                        Node::three(item1, (item2.0, value), lhs, mid, rhs),
-                        Some(item2.1),
-                    ),
-                    std::cmp::Ordering::Greater => (
-                        Node::four(item1, item2, (key, value), lhs, mid, Node::empty(), rhs),
-                        None,
-                    ),
-                },
-            }
-        } else {
-            match key.cmp(&item1.0) {
-                std::cmp::Ordering::Less => match *lhs {
-                    Node::Empty => {
-                        let lhs = Node::two((key, value), Node::empty(), Node::empty());
-                        (Node::three(item1, item2, lhs, mid, rhs), None)
-                    }
-                    Node::Two(two) => {
-                        let (lhs, replaced) = Node::insert2(two, key, value);
-                        (Node::three(item1, item2, lhs, mid, rhs), replaced)
-                    }
-                    Node::Three(three) => {
-                        let (lhs, replaced) = Node::insert3(three, key, value);
-                        (Node::three(item1, item2, lhs, mid, rhs), replaced)
-                    }
-                    Node::Four(four) => {
-                        let (mid_item, mid_lhs, mid_rhs) = Node::split_four(four);
-                        match key.cmp(&mid_item.0) {
-                            std::cmp::Ordering::Less => {
-                                let (mid_lhs, replaced) = mid_lhs.insert(key, value);
-                                (
-                                    Node::four(mid_item, item1, item2, mid_lhs, mid_rhs, mid, rhs),
-                                    replaced,
-                                )
-                            }
-                            std::cmp::Ordering::Equal => {
-                                let replaced = Some(mid_item.1);
-                                let mid_item = (mid_item.0, value);
-                                (
-                                    Node::four(mid_item, item1, item2, mid_lhs, mid_rhs, mid, rhs),
-                                    replaced,
-                                )
-                            }
-                            std::cmp::Ordering::Greater => {
-                                let (mid_rhs, replaced) = mid_rhs.insert(key, value);
-                                (
-                                    Node::four(mid_item, item1, item2, mid_lhs, mid_rhs, mid, rhs),
-                                    replaced,
-                                )
-                            }
+    pub fn nth(&self, mut index: usize) -> Option<&Item<K, V>> {
+        let mut node = self;
+        loop {
+            node = match node {
+                Node::Empty => return None,
+                Node::Two(two) => {
+                    let n = two.lhs.size();
+                    match index.cmp(&n) {
+                        std::cmp::Ordering::Less => &two.lhs,
+                        std::cmp::Ordering::Equal => return Some(&two.item),
+                        std::cmp::Ordering::Greater => {
+                            index -= n + 1;
+                            &two.rhs
                         }
                     }
-                },
-                std::cmp::Ordering::Equal => (
-                    Node::three((item1.0, value), item2, lhs, mid, rhs),
-                    Some(item1.1),
-                ),
-                std::cmp::Ordering::Greater => match key.cmp(&item2.0) {
-                    std::cmp::Ordering::Less => match *mid {
-                        Node::Empty => {
-                            let mid = Node::two((key, value), Node::empty(), Node::empty());
-                            (Node::three(item1, item2, lhs, mid, rhs), None)
-                        }
-                        Node::Two(two) => {
-                            let (mid, replaced) = Node::insert2(two, key, value);
-                            (Node::three(item1, item2, lhs, mid, rhs), replaced)
-                        }
-                        Node::Three(three) => {
-                            let (mid, replaced) = Node::insert3(three, key, value);
-                            (Node::three(item1, item2, lhs, mid, rhs), replaced)
-                        }
-                        Node::Four(four) => {
-                            let (mid_item, mid_lhs, mid_rhs) = Node::split_four(four);
-                            match key.cmp(&mid_item.0) {
-                                std::cmp::Ordering::Less => {
-                                    let (mid_lhs, replaced) = mid_lhs.insert(key, value);
-                                    (
-                                        Node::four(
-                                            item1, mid_item, item2, lhs, mid_lhs, mid_rhs, rhs,
-                                        ),
-                                        replaced,
-                                    )
-                                }
-                                std::cmp::Ordering::Equal => {
-                                    let replaced = Some(mid_item.1);
-                                    let mid_item = (mid_item.0, value);
-                                    (
-                                        Node::four(
-                                            item1, mid_item, item2, lhs, mid_lhs, mid_rhs, rhs,
-                                        ),
-                                        replaced,
-                                    )
-                                }
+                }
+                Node::Three(three) => {
+                    let n1 = three.lhs.size();
+                    match index.cmp(&n1) {
+                        std::cmp::Ordering::Less => &three.lhs,
+                        std::cmp::Ordering::Equal => return Some(&three.item1),
+                        std::cmp::Ordering::Greater => {
+                            index -= n1 + 1;
+                            let n2 = three.mid.size();
+                            match index.cmp(&n2) {
+                                std::cmp::Ordering::Less => &three.mid,
+                                std::cmp::Ordering::Equal => return Some(&three.item2),
                                 std::cmp::Ordering::Greater => {
-                                    let (mid_rhs, replaced) = mid_rhs.insert(key, value);
-                                    (
-                                        Node::four(
-                                            item1, mid_item, item2, lhs, mid_lhs, mid_rhs, rhs,
-                                        ),
-                                        replaced,
-                                    )
+                                    index -= n2 + 1;
+                                    &three.rhs
                                 }
                             }
                         }
-                    },
-                    std::cmp::Ordering::Equal => (
-                        Node::three(item1, (item2.0, value), lhs, mid, rhs),
-                        Some(item2.1),
-                    ),
-                    std::cmp::Ordering::Greater => match *rhs {
-                        Node::Empty => {
-                            let rhs = Node::two((key, value), Node::empty(), Node::empty());
-                            (Node::three(item1, item2, lhs, mid, rhs), None)
-                        }
-                        Node::Two(two) => {
-                            let (rhs, replaced) = Node::insert2(two, key, value);
-                            (Node::three(item1, item2, lhs, mid, rhs), replaced)
-                        }
-                        Node::Three(three) => {
-                            let (rhs, replaced) = Node::insert3(three, key, value);
-                            (Node::three(item1, item2, lhs, mid, rhs), replaced)
-                        }
-                        Node::Four(four) => {
-                            let (mid_item, mid_lhs, mid_rhs) = Node::split_four(four);
-                            match key.cmp(&mid_item.0) {
-                                std::cmp::Ordering::Less => {
-                                    let (mid_lhs, replaced) = mid_lhs.insert(key, value);
-                                    (
-                                        Node::four(
-                                            item1, item2, mid_item, lhs, mid, mid_lhs, mid_rhs,
-                                        ),
-                                        replaced,
-                                    )
-                                }
-                                std::cmp::Ordering::Equal => {
-                                    let replaced = Some(mid_item.1);
-                                    let mid_item = (mid_item.0, value);
-                                    (
-                                        Node::four(
-                                            item1, item2, mid_item, lhs, mid, mid_lhs, mid_rhs,
-                                        ),
-                                        replaced,
-                                    )
-                                }
+                    }
+                }
+                Node::Four(four) => {
+                    let n1 = four.lhs.size();
+                    match index.cmp(&n1) {
+                        std::cmp::Ordering::Less => &four.lhs,
+                        std::cmp::Ordering::Equal => return Some(&four.item1),
+                        std::cmp::Ordering::Greater => {
+                            index -= n1 + 1;
+                            let n2 = four.lhs_mid.size();
+                            match index.cmp(&n2) {
+                                std::cmp::Ordering::Less => &four.lhs_mid,
+                                std::cmp::Ordering::Equal => return Some(&four.item2),
                                 std::cmp::Ordering::Greater => {
-                                    let (mid_rhs, replaced) = mid_rhs.insert(key, value);
-                                    (
-                                        Node::four(
-                                            item1, item2, mid_item, lhs, mid, mid_lhs, mid_rhs,
-                                        ),
-                                        replaced,
-                                    )
+                                    index -= n2 + 1;
+                                    let n3 = four.rhs_mid.size();
+                                    match index.cmp(&n3) {
+                                        std::cmp::Ordering::Less => &four.rhs_mid,
+                                        std::cmp::Ordering::Equal => return Some(&four.item3),
+                                        std::cmp::Ordering::Greater => {
+                                            index -= n3 + 1;
+                                            &four.rhs
+                                        }
+                                    }
                                 }
                             }
                         }
-                    },
-                },
-            }
-        }
-    }
-
-    fn split_four(four: Four<K, V>) -> (Item<K, V>, NodeBox<K, V>, NodeBox<K, V>) {
-        let Four {
-            size: _,
-            item1,
-            item2,
-            item3,
-            lhs,
-            lhs_mid,
-            rhs_mid,
-            rhs,
-        } = four;
-        (
-            item2,
-            Node::two(item1, lhs, lhs_mid),
-            Node::two(item3, rhs_mid, rhs),
-        )
-    }
-
-    fn remove(self, key: &K) -> (NodeBox<K, V>, Option<V>, bool) {
-        match self {
-            Node::Empty => (Node::empty(), None, false),
-            Node::Two(two) => Node::remove2(two, key),
-            Node::Three(three) => Node::remove3(three, key),
-            Node::Four(four) => Node::remove4(four, key),
-        }
-    }
-
-    fn remove2(two: Two<K, V>, key: &K) -> (NodeBox<K, V>, Option<V>, bool) {
-        let Two {
-            size: _,
-            item,
-            lhs,
-            rhs,
-        } = two;
-        match key.cmp(&item.0) {
-            std::cmp::Ordering::Less => {
-                let (lhs, result, reduced) = Node::remove(*lhs, key);
-                match reduced {
-                    true => {
-                        let (node, reduced) = Node::fix2_lhs(item, lhs, rhs);
-                        (node, result, reduced)
-                    }
-                    false => (Node::two(item, lhs, rhs), result, false),
-                }
-            }
-            std::cmp::Ordering::Equal => {
-                if let Some((small, rhs, reduced)) = Node::remove_smallest(*rhs) {
-                    match reduced {
-                        true => {
-                            let (node, reduced) = Node::fix2_rhs(small, lhs, rhs);
-                            (node, Some(item.1), reduced)
-                        }
-                        false => (Node::two(small, lhs, rhs), Some(item.1), false),
-                    }
-                } else {
-                    (lhs, Some(item.1), true)
-                }
-            }
-            std::cmp::Ordering::Greater => {
-                let (rhs, result, reduced) = Node::remove(*rhs, key);
-                match reduced {
-                    true => {
-                        let (node, reduced) = Node::fix2_rhs(item, lhs, rhs);
-                        (node, result, reduced)
                     }
-                    false => (Node::two(item, lhs, rhs), result, false),
                 }
-            }
+            };
         }
     }
 
-    fn remove3(three: Three<K, V>, key: &K) -> (NodeBox<K, V>, Option<V>, bool) {
-        let Three {
-            size: _,
-            item1,
-            item2,
-            lhs,
-            mid,
-            rhs,
-        } = three;
-        match key.cmp(&item1.0) {
-            std::cmp::Ordering::Less => {
-                let (lhs, result, reduced) = Node::remove(*lhs, key);
-                match reduced {
-                    true => {
-                        let (node, reduced) = Node::fix3_lhs(item1, item2, lhs, mid, rhs);
-                        (node, result, reduced)
-                    }
-                    false => (Node::three(item1, item2, lhs, mid, rhs), result, false),
-                }
-            }
-            std::cmp::Ordering::Equal => {
-                let result = Some(item1.1);
-                if let Some((small, mid, reduced)) = Node::remove_smallest(*mid) {
-                    match reduced {
-                        true => {
-                            let (node, reduced) = Node::fix3_mid(small, item2, lhs, mid, rhs);
-                            (node, result, reduced)
-                        }
-                        false => (Node::three(small, item2, lhs, mid, rhs), result, false),
-                    }
-                } else {
-                    (Node::two(item2, lhs, rhs), result, false)
-                }
-            }
-            std::cmp::Ordering::Greater => match key.cmp(&item2.0) {
-                std::cmp::Ordering::Less => {
-                    let (mid, result, reduced) = Node::remove(*mid, key);
-                    match reduced {
-                        true => {
-                            let (node, reduced) = Node::fix3_mid(item1, item2, lhs, mid, rhs);
-                            (node, result, reduced)
+    fn nth_mut(&mut self, mut index: usize) -> Option<&mut Item<K, V>> {
+        let mut node = self;
+        loop {
+            node = match node {
+                Node::Empty => return None,
+                Node::Two(two) => {
+                    let n = two.lhs.size();
+                    match index.cmp(&n) {
+                        std::cmp::Ordering::Less => two.lhs.as_mut()?,
+                        std::cmp::Ordering::Equal => return Some(&mut two.item),
+                        std::cmp::Ordering::Greater => {
+                            index -= n + 1;
+                            two.rhs.as_mut()?
                         }
-                        false => (Node::three(item1, item2, lhs, mid, rhs), result, false),
                     }
                 }
-                std::cmp::Ordering::Equal => {
-                    let result = Some(item2.1);
-                    if let Some((small, rhs, reduced)) = Node::remove_smallest(*rhs) {
-                        match reduced {
-                            true => {
-                                let (node, reduced) = Node::fix3_rhs(item1, small, lhs, mid, rhs);
-                                (node, result, reduced)
+                Node::Three(three) => {
+                    let n1 = three.lhs.size();
+                    match index.cmp(&n1) {
+                        std::cmp::Ordering::Less => three.lhs.as_mut()?,
+                        std::cmp::Ordering::Equal => return Some(&mut three.item1),
+                        std::cmp::Ordering::Greater => {
+                            index -= n1 + 1;
+                            let n2 = three.mid.size();
+                            match index.cmp(&n2) {
+                                std::cmp::Ordering::Less => three.mid.as_mut()?,
+                                std::cmp::Ordering::Equal => return Some(&mut three.item2),
+                                std::cmp::Ordering::Greater => {
+                                    index -= n2 + 1;
+                                    three.rhs.as_mut()?
+                                }
                             }
-                            false => (Node::three(item1, small, lhs, mid, rhs), result, false),
                         }
-                    } else {
-                        (Node::two(item1, lhs, mid), result, false)
                     }
                 }
-                std::cmp::Ordering::Greater => {
-                    let (rhs, result, reduced) = Node::remove(*rhs, key);
-                    match reduced {
-                        true => {
-                            let (node, reduced) = Node::fix3_rhs(item1, item2, lhs, mid, rhs);
-                            (node, result, reduced)
+                Node::Four(four) => {
+                    let n1 = four.lhs.size();
+                    match index.cmp(&n1) {
+                        std::cmp::Ordering::Less => four.lhs.as_mut()?,
+                        std::cmp::Ordering::Equal => return Some(&mut four.item1),
+                        std::cmp::Ordering::Greater => {
+                            index -= n1 + 1;
+                            let n2 = four.lhs_mid.size();
+                            match index.cmp(&n2) {
+                                std::cmp::Ordering::Less => four.lhs_mid.as_mut()?,
+                                std::cmp::Ordering::Equal => return Some(&mut four.item2),
+                                std::cmp::Ordering::Greater => {
+                                    index -= n2 + 1;
+                                    let n3 = four.rhs_mid.size();
+                                    match index.cmp(&n3) {
+                                        std::cmp::Ordering::Less => four.rhs_mid.as_mut()?,
+                                        std::cmp::Ordering::Equal => return Some(&mut four.item3),
+                                        std::cmp::Ordering::Greater => {
+                                            index -= n3 + 1;
+                                            four.rhs.as_mut()?
+                                        }
+                                    }
+                                }
+                            }
                         }
-                        false => (Node::three(item1, item2, lhs, mid, rhs), result, false),
                     }
                 }
-            },
+            };
         }
     }
 
-    fn remove4(four: Four<K, V>, key: &K) -> (NodeBox<K, V>, Option<V>, bool) {
-        let Four {
-            size: _,
-            item1,
-            item2,
-            item3,
-            lhs,
-            lhs_mid,
-            rhs_mid,
-            rhs,
-        } = four;
-        match key.cmp(&item2.0) {
-            std::cmp::Ordering::Less => match key.cmp(&item1.0) {
-                std::cmp::Ordering::Less => {
-                    let (lhs, result, reduced) = Node::remove(*lhs, key);
-                    match reduced {
-                        true => {
-                            let (node, reduced) =
-                                Node::fix4_lhs(item1, item2, item3, lhs, lhs_mid, rhs_mid, rhs);
-                            (node, result, reduced)
-                        }
-                        false => (
-                            Node::four(item1, item2, item3, lhs, lhs_mid, rhs_mid, rhs),
-                            result,
-                            false,
-                        ),
-                    }
-                }
-                std::cmp::Ordering::Equal => {
-                    let result = Some(item1.1);
-                    if let Some((small, lhs_mid, reduced)) = Node::remove_smallest(*lhs_mid) {
-                        match reduced {
-                            true => {
-                                let (node, reduced) = Node::fix4_lhs_mid(
-                                    small, item2, item3, lhs, lhs_mid, rhs_mid, rhs,
-                                );
-                                (node, result, reduced)
-                            }
-                            false => (
-                                Node::four(small, item2, item3, lhs, lhs_mid, rhs_mid, rhs),
-                                result,
-                                false,
-                            ),
-                        }
-                    } else {
-                        (Node::three(item2, item3, lhs, rhs_mid, rhs), result, false)
-                    }
-                }
-                std::cmp::Ordering::Greater => {
-                    let (lhs_mid, result, reduced) = Node::remove(*lhs_mid, key);
-                    match reduced {
-                        true => {
-                            let (node, reduced) =
-                                Node::fix4_lhs_mid(item1, item2, item3, lhs, lhs_mid, rhs_mid, rhs);
-                            (node, result, reduced)
-                        }
-                        false => (
-                            Node::four(item1, item2, item3, lhs, lhs_mid, rhs_mid, rhs),
-                            result,
-                            false,
-                        ),
-                    }
-                }
+    fn locate_mut<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<&mut Item<K, V>>
+    where
+        K: Borrow<Q>,
+    {
+        let mut node = self;
+        loop {
+            node = match node {
+                Node::Empty => return None,
+                Node::Two(two) => match key.cmp(two.item.0.borrow()) {
+                    std::cmp::Ordering::Less => two.lhs.as_mut()?,
+                    std::cmp::Ordering::Equal => return Some(&mut two.item),
+                    std::cmp::Ordering::Greater => two.rhs.as_mut()?,
+                },
+                Node::Three(three) => match key.cmp(three.item1.0.borrow()) {
+                    std::cmp::Ordering::Less => three.lhs.as_mut()?,
+                    std::cmp::Ordering::Equal => return Some(&mut three.item1),
+                    std::cmp::Ordering::Greater => match key.cmp(three.item2.0.borrow()) {
+                        std::cmp::Ordering::Less => three.mid.as_mut()?,
+                        std::cmp::Ordering::Equal => return Some(&mut three.item2),
+                        std::cmp::Ordering::Greater => three.rhs.as_mut()?,
+                    },
+                },
+                Node::Four(four) => match key.cmp(four.item1.0.borrow()) {
+                    std::cmp::Ordering::Less => four.lhs.as_mut()?,
+                    std::cmp::Ordering::Equal => return Some(&mut four.item1),
+                    std::cmp::Ordering::Greater => match key.cmp(four.item2.0.borrow()) {
+                        std::cmp::Ordering::Less => four.lhs_mid.as_mut()?,
+                        std::cmp::Ordering::Equal => return Some(&mut four.item2),
+                        std::cmp::Ordering::Greater => match key.cmp(four.item3.0.borrow()) {
+                            std::cmp::Ordering::Less => four.rhs_mid.as_mut()?,
+                            std::cmp::Ordering::Equal => return Some(&mut four.item3),
+                            std::cmp::Ordering::Greater => four.rhs.as_mut()?,
+                        },
+                    },
+                },
+            };
+        }
+    }
+
+    pub fn rank<Q: Ord + ?Sized>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        match self {
+            Node::Empty => 0,
+            Node::Two(two) => match key.cmp(two.item.0.borrow()) {
+                std::cmp::Ordering::Less => two.lhs.rank(key),
+                std::cmp::Ordering::Equal => two.lhs.size(),
+                std::cmp::Ordering::Greater => 1 + two.lhs.size() + two.rhs.rank(key),
             },
-            std::cmp::Ordering::Equal => {
-                let result = Some(item2.1);
-                if let Some((small, rhs_mid, reduced)) = Node::remove_smallest(*rhs_mid) {
-                    match reduced {
-                        true => {
-                            let (node, reduced) =
-                                Node::fix4_rhs_mid(item1, small, item3, lhs, lhs_mid, rhs_mid, rhs);
-                            (node, result, reduced)
+            Node::Three(three) => {
+                let n1 = three.lhs.size() + 1;
+                match key.cmp(three.item1.0.borrow()) {
+                    std::cmp::Ordering::Less => three.lhs.rank(key),
+                    std::cmp::Ordering::Equal => n1 - 1,
+                    std::cmp::Ordering::Greater => match key.cmp(three.item2.0.borrow()) {
+                        std::cmp::Ordering::Less => n1 + three.mid.rank(key),
+                        std::cmp::Ordering::Equal => n1 + three.mid.size(),
+                        std::cmp::Ordering::Greater => {
+                            n1 + three.mid.size() + 1 + three.rhs.rank(key)
                         }
-                        false => (
-                            Node::four(item1, small, item3, lhs, lhs_mid, rhs_mid, rhs),
-                            result,
-                            false,
-                        ),
-                    }
-                } else {
-                    (Node::three(item1, item3, lhs, lhs_mid, rhs), result, false)
+                    },
                 }
             }
-            std::cmp::Ordering::Greater => match key.cmp(&item3.0) {
-                std::cmp::Ordering::Less => {
-                    let (rhs_mid, result, reduced) = Node::remove(*rhs_mid, key);
-                    match reduced {
-                        true => {
-                            let (node, reduced) =
-                                Node::fix4_rhs_mid(item1, item2, item3, lhs, lhs_mid, rhs_mid, rhs);
-                            (node, result, reduced)
-                        }
-                        false => (
-                            Node::four(item1, item2, item3, lhs, lhs_mid, rhs_mid, rhs),
-                            result,
-                            false,
-                        ),
-                    }
-                }
-                std::cmp::Ordering::Equal => {
-                    let result = Some(item3.1);
-                    if let Some((small, rhs, reduced)) = Node::remove_smallest(*rhs) {
-                        match reduced {
-                            true => {
-                                let (node, reduced) =
-                                    Node::fix4_rhs(item1, item2, small, lhs, lhs_mid, rhs_mid, rhs);
-                                (node, result, reduced)
-                            }
-                            false => (
-                                Node::four(item1, item2, small, lhs, lhs_mid, rhs_mid, rhs),
-                                result,
-                                false,
-                            ),
-                        }
-                    } else {
-                        (
-                            Node::three(item1, item2, lhs, lhs_mid, rhs_mid),
-                            result,
-                            false,
-                        )
-                    }
-                }
-                std::cmp::Ordering::Greater => {
-                    let (rhs, result, reduced) = Node::remove(*rhs, key);
-                    match reduced {
-                        true => {
-                            let (node, reduced) =
-                                Node::fix4_rhs(item1, item2, item3, lhs, lhs_mid, rhs_mid, rhs);
-                            (node, result, reduced)
+            Node::Four(four) => {
+                let n1 = four.lhs.size() + 1;
+                match key.cmp(four.item1.0.borrow()) {
+                    std::cmp::Ordering::Less => four.lhs.rank(key),
+                    std::cmp::Ordering::Equal => n1 - 1,
+                    std::cmp::Ordering::Greater => {
+                        let n2 = n1 + four.lhs_mid.size() + 1;
+                        match key.cmp(four.item2.0.borrow()) {
+                            std::cmp::Ordering::Less => n1 + four.lhs_mid.rank(key),
+                            std::cmp::Ordering::Equal => n2 - 1,
+                            std::cmp::Ordering::Greater => match key.cmp(four.item3.0.borrow()) {
+                                std::cmp::Ordering::Less => n2 + four.rhs_mid.rank(key),
+                                std::cmp::Ordering::Equal => n2 + four.rhs_mid.size(),
+                                std::cmp::Ordering::Greater => {
+                                    n2 + four.rhs_mid.size() + 1 + four.rhs.rank(key)
+                                }
+                            },
                         }
-                        false => (
-                            Node::four(item1, item2, item3, lhs, lhs_mid, rhs_mid, rhs),
-                            result,
-                            false,
-                        ),
                     }
                 }
-            },
+            }
         }
     }
 
-    fn remove_smallest(self: Node<K, V>) -> Option<(Item<K, V>, NodeBox<K, V>, bool)> {
+    pub fn first(&self) -> Option<&Item<K, V>> {
         match self {
             Node::Empty => None,
-            Node::Two(two) => Node::remove_smallest2(two),
-            Node::Three(three) => Node::remove_smallest3(three),
-            Node::Four(four) => Node::remove_smallest4(four),
+            Node::Two(two) => two.lhs.first().or(Some(&two.item)),
+            Node::Three(three) => three.lhs.first().or(Some(&three.item1)),
+            Node::Four(four) => four.lhs.first().or(Some(&four.item1)),
         }
     }
 
-    fn remove_smallest2(two: Two<K, V>) -> Option<(Item<K, V>, NodeBox<K, V>, bool)> {
-        let Two {
-            size: _,
-            item,
-            lhs,
-            rhs,
-        } = two;
-        if lhs.is_empty() {
-            Some((item, rhs, true))
-        } else {
-            let (small, lhs, reduced) = Node::remove_smallest(*lhs).unwrap();
-            match reduced {
-                true => {
-                    let (node, reduced) = Node::fix2_lhs(item, lhs, rhs);
-                    Some((small, node, reduced))
-                }
-                false => Some((small, Node::two(item, lhs, rhs), false)),
-            }
+    pub fn last(&self) -> Option<&Item<K, V>> {
+        match self {
+            Node::Empty => None,
+            Node::Two(two) => two.rhs.last().or(Some(&two.item)),
+            Node::Three(three) => three.rhs.last().or(Some(&three.item2)),
+            Node::Four(four) => four.rhs.last().or(Some(&four.item3)),
         }
     }
 
-    fn remove_smallest3(three: Three<K, V>) -> Option<(Item<K, V>, NodeBox<K, V>, bool)> {
-        let Three {
-            size: _,
-            item1,
-            item2,
-            lhs,
-            mid,
-            rhs,
-        } = three;
-        if lhs.is_empty() {
-            Some((item1, Node::two(item2, mid, rhs), false))
-        } else {
-            let (small, lhs, reduced) = Node::remove_smallest(*lhs).unwrap();
-            match reduced {
-                true => {
-                    let (node, reduced) = Node::fix3_lhs(item1, item2, lhs, mid, rhs);
-                    Some((small, node, reduced))
-                }
-                false => Some((small, Node::three(item1, item2, lhs, mid, rhs), false)),
+    // The largest item with a key less than (or, if `inclusive`, equal to) `key`.
+    fn lower_neighbour<Q: Ord + ?Sized>(&self, key: &Q, inclusive: bool) -> Option<&Item<K, V>>
+    where
+        K: Borrow<Q>,
+    {
+        match self {
+            Node::Empty => None,
+            Node::Two(two) => {
+                Node::lower_neighbour_parts(&[&two.item], &[&two.lhs, &two.rhs], key, inclusive)
             }
+            Node::Three(three) => Node::lower_neighbour_parts(
+                &[&three.item1, &three.item2],
+                &[&three.lhs, &three.mid, &three.rhs],
+                key,
+                inclusive,
+            ),
+            Node::Four(four) => Node::lower_neighbour_parts(
+                &[&four.item1, &four.item2, &four.item3],
+                &[&four.lhs, &four.lhs_mid, &four.rhs_mid, &four.rhs],
+                key,
+                inclusive,
+            ),
         }
     }
 
-    fn remove_smallest4(four: Four<K, V>) -> Option<(Item<K, V>, NodeBox<K, V>, bool)> {
-        let Four {
-            size: _,
-            item1,
-            item2,
-            item3,
-            lhs,
-            lhs_mid,
-            rhs_mid,
-            rhs,
-        } = four;
-        if lhs.is_empty() {
-            Some((
-                item1,
-                Node::three(item2, item3, lhs_mid, rhs_mid, rhs),
-                false,
-            ))
-        } else {
-            let (small, lhs, reduced) = Node::remove_smallest(*lhs).unwrap();
-            match reduced {
-                true => {
-                    let (node, reduced) =
-                        Node::fix4_lhs(item1, item2, item3, lhs, lhs_mid, rhs_mid, rhs);
-                    Some((small, node, reduced))
+    fn lower_neighbour_parts<'a, Q: Ord + ?Sized>(
+        node_items: &[&'a Item<K, V>],
+        children: &[&'a NodeBox<K, V, A>],
+        key: &Q,
+        inclusive: bool,
+    ) -> Option<&'a Item<K, V>>
+    where
+        K: Borrow<Q>,
+    {
+        let i = node_items
+            .iter()
+            .take_while(|item| item.0.borrow() < key || (inclusive && item.0.borrow() == key))
+            .count();
+        if i > 0 && node_items[i - 1].0.borrow() == key {
+            return Some(node_items[i - 1]);
+        }
+        children[i]
+            .lower_neighbour(key, inclusive)
+            .or(i.checked_sub(1).map(|j| node_items[j]))
+    }
+
+    // The smallest item with a key greater than (or, if `inclusive`, equal to) `key`.
+    fn upper_neighbour<Q: Ord + ?Sized>(&self, key: &Q, inclusive: bool) -> Option<&Item<K, V>>
+    where
+        K: Borrow<Q>,
+    {
+        match self {
+            Node::Empty => None,
+            Node::Two(two) => {
+                Node::upper_neighbour_parts(&[&two.item], &[&two.lhs, &two.rhs], key, inclusive)
+            }
+            Node::Three(three) => Node::upper_neighbour_parts(
+                &[&three.item1, &three.item2],
+                &[&three.lhs, &three.mid, &three.rhs],
+                key,
+                inclusive,
+            ),
+            Node::Four(four) => Node::upper_neighbour_parts(
+                &[&four.item1, &four.item2, &four.item3],
+                &[&four.lhs, &four.lhs_mid, &four.rhs_mid, &four.rhs],
+                key,
+                inclusive,
+            ),
+        }
+    }
+
+    fn upper_neighbour_parts<'a, Q: Ord + ?Sized>(
+        node_items: &[&'a Item<K, V>],
+        children: &[&'a NodeBox<K, V, A>],
+        key: &Q,
+        inclusive: bool,
+    ) -> Option<&'a Item<K, V>>
+    where
+        K: Borrow<Q>,
+    {
+        let i = node_items
+            .iter()
+            .take_while(|item| item.0.borrow() < key || (!inclusive && item.0.borrow() == key))
+            .count();
+        if i < node_items.len() && node_items[i].0.borrow() == key {
+            return Some(node_items[i]);
+        }
+        children[i]
+            .upper_neighbour(key, inclusive)
+            .or(node_items.get(i).copied())
+    }
+
+    // The index of the item whose key equals `key`, or else of the child to
+    // descend into.
+    fn search<Q: Ord + ?Sized>(&self, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+    {
+        for i in 0..self.len() {
+            match key.cmp(self.item(i).0.borrow()) {
+                std::cmp::Ordering::Less => return Err(i),
+                std::cmp::Ordering::Equal => return Ok(i),
+                std::cmp::Ordering::Greater => {}
+            }
+        }
+        Err(self.len())
+    }
+
+    // Insert in place without recursion: walk down recording the path, then
+    // carry any split back up it, restructuring only nodes that overflow.
+    fn insert(root: &mut NodeBox<K, V, A>, key: K, value: V) -> Option<V> {
+        let mut path = Path::new();
+        let mut link: *mut NodeBox<K, V, A> = root;
+        let mut replaced = None;
+        let mut carry = loop {
+            // SAFETY: `link` is `root` or a child link of the node reached
+            // before it, and nothing else borrows the tree during the descent.
+            let Some(node) = (unsafe { (*link).as_mut() }) else {
+                break Some(((key, value), Node::empty()));
+            };
+            match node.search(&key) {
+                Ok(i) => {
+                    replaced = Some(std::mem::replace(&mut node.item_mut(i).1, value));
+                    node.refresh();
+                    break None;
+                }
+                Err(i) => {
+                    path.push(link, i);
+                    link = node.child_mut(i);
+                }
+            }
+        };
+        while let Some((link, i)) = path.pop() {
+            // SAFETY: the nodes behind the links still on the path have not
+            // moved, and the deeper link borrowed last time round is dead.
+            let link = unsafe { &mut *link };
+            carry = match carry {
+                Some((item, rhs)) => Node::absorb(link, i, item, rhs),
+                None => {
+                    link.as_mut().unwrap().refresh();
+                    None
+                }
+            };
+        }
+        if let Some((item, rhs)) = carry {
+            let lhs = std::mem::replace(root, Node::empty());
+            *root = Node::two(item, lhs, rhs);
+        }
+        replaced
+    }
+
+    // Add an item, and the subtree to its right, after child `i`. A full
+    // node keeps its lower half and hands the rest back, for its parent to
+    // absorb in turn.
+    fn absorb(
+        node: &mut NodeBox<K, V, A>,
+        i: usize,
+        item: Item<K, V>,
+        rhs: NodeBox<K, V, A>,
+    ) -> Option<Grafted<K, V, A>> {
+        let mut parts = Parts::take(node);
+        parts.insert(i, item, i + 1, rhs);
+        if parts.len < 4 {
+            node.set(parts.into_node());
+            return None;
+        }
+        let Parts {
+            len: _,
+            items: [item1, item2, item3, item4],
+            children: [lhs, lhs_mid, mid, rhs_mid, rhs],
+        } = parts;
+        node.set(Node::new2(item1.unwrap(), lhs.unwrap(), lhs_mid.unwrap()));
+        let upper = Node::three(
+            item3.unwrap(),
+            item4.unwrap(),
+            mid.unwrap(),
+            rhs_mid.unwrap(),
+            rhs.unwrap(),
+        );
+        Some((item2.unwrap(), upper))
+    }
+
+    fn remove<T: Target<K>>(root: &mut NodeBox<K, V, A>, target: &mut T) -> Option<Item<K, V>> {
+        Node::remove_with(root, |node| {
+            let mut before = 0;
+            for i in 0..node.len() {
+                let n = before + node.child(i).size();
+                match target.compare(&node.item(i).0, n) {
+                    std::cmp::Ordering::Less => {
+                        target.descend(before);
+                        return Step::Descend(i);
+                    }
+                    std::cmp::Ordering::Equal => return Step::Take(i),
+                    std::cmp::Ordering::Greater => before = n + 1,
+                }
+            }
+            target.descend(before);
+            Step::Descend(node.len())
+        })
+    }
+
+    fn remove_first(root: &mut NodeBox<K, V, A>) -> Option<Item<K, V>> {
+        Node::remove_with(root, |node| {
+            if node.child(0).is_empty() {
+                Step::Take(0)
+            } else {
+                Step::Descend(0)
+            }
+        })
+    }
+
+    fn remove_last(root: &mut NodeBox<K, V, A>) -> Option<Item<K, V>> {
+        Node::remove_with(root, |node| {
+            let n = node.len();
+            if node.child(n).is_empty() {
+                Step::Take(n - 1)
+            } else {
+                Step::Descend(n)
+            }
+        })
+    }
+
+    // Remove in place without recursion. `step` chooses, at each node on the
+    // way down, the item to take or the child to descend into. An internal
+    // item is swapped for its successor, which is taken from a leaf, and the
+    // path is then repaired bottom-up.
+    fn remove_with<F: FnMut(&Node<K, V, A>) -> Step>(
+        root: &mut NodeBox<K, V, A>,
+        mut step: F,
+    ) -> Option<Item<K, V>> {
+        let mut path = Path::new();
+        let mut link: *mut NodeBox<K, V, A> = root;
+        let mut found = None;
+        let (mut item, mut reduced) = loop {
+            // SAFETY: as in `insert`, `link` is `root` or a child link of the
+            // node reached before it, and the tree is borrowed only here.
+            let node = unsafe { (*link).as_mut() }?;
+            let next = match found {
+                None => step(node),
+                Some(_) if node.child(0).is_empty() => Step::Take(0),
+                Some(_) => Step::Descend(0),
+            };
+            match next {
+                Step::Take(i) if node.child(0).is_empty() => {
+                    // SAFETY: `node` is not used again, so `link` is the only
+                    // live reference into this leaf.
+                    break Node::remove_leaf(unsafe { &mut *link }, i);
+                }
+                Step::Take(i) => {
+                    found = Some((path.len, i));
+                    path.push(link, i + 1);
+                    link = node.child_mut(i + 1);
+                }
+                Step::Descend(i) => {
+                    path.push(link, i);
+                    link = node.child_mut(i);
+                }
+            }
+        };
+        while let Some((link, i)) = path.pop() {
+            // SAFETY: as in `insert`, links are taken deepest first, so the
+            // one borrowed last time round is no longer in use.
+            let link = unsafe { &mut *link };
+            if let Some((depth, j)) = found {
+                if depth == path.len {
+                    item = std::mem::replace(link.as_mut().unwrap().item_mut(j), item);
                 }
-                false => Some((
-                    small,
-                    Node::four(item1, item2, item3, lhs, lhs_mid, rhs_mid, rhs),
-                    false,
-                )),
             }
+            reduced = Node::settle(link, i, reduced);
         }
+        Some(item)
     }
 
-    pub fn visit<Visitor: FnMut(&Item<K, V>)>(&self, visitor: &mut Visitor) {
+    // Remove item `i` from a leaf, reporting whether the leaf is now empty.
+    fn remove_leaf(node: &mut NodeBox<K, V, A>, i: usize) -> (Item<K, V>, bool) {
+        let mut parts = Parts::take(node);
+        let (item, _empty) = parts.remove(i, i + 1);
+        let reduced = parts.len == 0;
+        node.set(if reduced {
+            Node::Empty
+        } else {
+            parts.into_node()
+        });
+        (item, reduced)
+    }
+
+    // Bring this node up to date after a removal beneath child `i`, and
+    // report whether it lost height in turn.
+    fn settle(node: &mut NodeBox<K, V, A>, i: usize, reduced: bool) -> bool {
+        if reduced {
+            Node::fix(node, i)
+        } else {
+            node.as_mut().unwrap().refresh();
+            false
+        }
+    }
+
+    // Child `i` is one level shorter than its siblings. Borrow an item through
+    // a neighbouring sibling if it can spare one, otherwise merge the child
+    // into that sibling, which may leave this node short in turn.
+    fn fix(node: &mut NodeBox<K, V, A>, i: usize) -> bool {
+        let mut parts = Parts::take(node);
+        let (s, sep) = if i < parts.len {
+            (i + 1, i)
+        } else {
+            (i - 1, i - 1)
+        };
+        let sibling = parts.children[s].as_mut().unwrap();
+        if sibling.len() > 1 {
+            let mut spare = Parts::take(sibling);
+            let (item, grandchild) = if s > i {
+                spare.remove(0, 0)
+            } else {
+                spare.remove(spare.len - 1, spare.len)
+            };
+            sibling.set(spare.into_node());
+            let separator = std::mem::replace(parts.items[sep].as_mut().unwrap(), item);
+            let child = parts.children[i].take().unwrap();
+            parts.children[i] = Some(if s > i {
+                Node::two(separator, child, grandchild)
+            } else {
+                Node::two(separator, grandchild, child)
+            });
+            node.set(parts.into_node());
+            return false;
+        }
+        let (separator, child) = parts.remove(sep, i);
+        let sibling = parts.children[sep].as_mut().unwrap();
+        let mut merged = Parts::take(sibling);
+        if s > i {
+            merged.insert(0, separator, 0, child);
+        } else {
+            merged.insert(1, separator, 2, child);
+        }
+        sibling.set(merged.into_node());
+        if parts.len == 0 {
+            *node = parts.children[0].take().unwrap();
+            true
+        } else {
+            node.set(parts.into_node());
+            false
+        }
+    }
+
+    // Build a subtree of the given height from the next `count` items, which
+    // must lie between the sizes of the sparsest and fullest such subtrees.
+    fn build<I: Iterator<Item = Item<K, V>>>(
+        items: &mut I,
+        count: usize,
+        height: usize,
+    ) -> NodeBox<K, V, A> {
+        if height == 0 {
+            return Node::empty();
+        }
+        let sparsest = 2usize.saturating_pow(height as u32 - 1) - 1;
+        let arity = (2..=4)
+            .rev()
+            .find(|k| count + 1 >= k * (sparsest + 1))
+            .unwrap();
+        let share = (count + 1 - arity) / arity;
+        let extra = (count + 1 - arity) % arity;
+        let size = |i: usize| share + (i < extra) as usize;
+        let lhs = Node::build(items, size(0), height - 1);
+        let item1 = items.next().unwrap();
+        let mid = Node::build(items, size(1), height - 1);
+        if arity == 2 {
+            return Node::two(item1, lhs, mid);
+        }
+        let item2 = items.next().unwrap();
+        let mid2 = Node::build(items, size(2), height - 1);
+        if arity == 3 {
+            return Node::three(item1, item2, lhs, mid, mid2);
+        }
+        let item3 = items.next().unwrap();
+        let rhs = Node::build(items, size(3), height - 1);
+        Node::four(item1, item2, item3, lhs, mid, mid2, rhs)
+    }
+
+    fn height(&self) -> usize {
         match self {
-            Node::Empty => {}
+            Node::Empty => 0,
+            Node::Two(two) => 1 + two.lhs.height(),
+            Node::Three(three) => 1 + three.lhs.height(),
+            Node::Four(four) => 1 + four.lhs.height(),
+        }
+    }
+
+    // Join two subtrees, with every key in `lhs` less than `item`'s and every
+    // key in `rhs` greater, in time proportional to the difference in their
+    // heights.
+    fn join(lhs: Subtree<K, V, A>, item: Item<K, V>, rhs: Subtree<K, V, A>) -> Subtree<K, V, A> {
+        let height = std::cmp::max(lhs.1, rhs.1);
+        let grown = if lhs.1 >= rhs.1 {
+            Node::join_right(lhs, item, rhs)
+        } else {
+            Node::join_left(lhs, item, rhs)
+        };
+        match grown {
+            Grown::Fits(node) => (node, height),
+            Grown::Split(lhs, item, rhs) => (Node::two(item, lhs, rhs), height + 1),
+        }
+    }
+
+    // Graft `rhs` and `item` onto the right spine of the taller `lhs`.
+    fn join_right(
+        lhs: Subtree<K, V, A>,
+        item: Item<K, V>,
+        rhs: Subtree<K, V, A>,
+    ) -> Grown<K, V, A> {
+        let (node, height) = lhs;
+        if height == rhs.1 {
+            return Grown::Split(node, item, rhs.0);
+        }
+        match node.into_node() {
+            Node::Empty => unreachable!(),
             Node::Two(two) => {
                 let Two {
                     size: _,
-                    item,
+                    summary: _,
+                    item: item1,
                     lhs,
-                    rhs,
+                    rhs: last,
                 } = two;
-                lhs.visit(visitor);
-                visitor(item);
-                rhs.visit(visitor);
+                match Node::join_right((last, height - 1), item, rhs) {
+                    Grown::Fits(last) => Grown::Fits(Node::two(item1, lhs, last)),
+                    Grown::Split(a, x, b) => Grown::Fits(Node::three(item1, x, lhs, a, b)),
+                }
             }
             Node::Three(three) => {
                 let Three {
                     size: _,
+                    summary: _,
                     item1,
                     item2,
                     lhs,
                     mid,
-                    rhs,
+                    rhs: last,
                 } = three;
-                lhs.visit(visitor);
-                visitor(item1);
-                mid.visit(visitor);
-                visitor(item2);
-                rhs.visit(visitor);
+                match Node::join_right((last, height - 1), item, rhs) {
+                    Grown::Fits(last) => Grown::Fits(Node::three(item1, item2, lhs, mid, last)),
+                    Grown::Split(a, x, b) => {
+                        Grown::Fits(Node::four(item1, item2, x, lhs, mid, a, b))
+                    }
+                }
             }
             Node::Four(four) => {
                 let Four {
                     size: _,
+                    summary: _,
                     item1,
                     item2,
                     item3,
                     lhs,
                     lhs_mid,
                     rhs_mid,
-                    rhs,
+                    rhs: last,
                 } = four;
-                lhs.visit(visitor);
-                visitor(item1);
-                lhs_mid.visit(visitor);
-                visitor(item2);
-                rhs_mid.visit(visitor);
-                visitor(item3);
-                rhs.visit(visitor);
+                match Node::join_right((last, height - 1), item, rhs) {
+                    Grown::Fits(last) => {
+                        Grown::Fits(Node::four(item1, item2, item3, lhs, lhs_mid, rhs_mid, last))
+                    }
+                    Grown::Split(a, x, b) => Grown::Split(
+                        Node::two(item1, lhs, lhs_mid),
+                        item2,
+                        Node::three(item3, x, rhs_mid, a, b),
+                    ),
+                }
             }
         }
     }
 
-    fn fix2_lhs(
-        orig_item: Item<K, V>,
-        orig_lhs: NodeBox<K, V>,
-        orig_rhs: NodeBox<K, V>,
-    ) -> (NodeBox<K, V>, bool) {
-        match *orig_rhs {
+    // Graft `lhs` and `item` onto the left spine of the taller `rhs`.
+    fn join_left(lhs: Subtree<K, V, A>, item: Item<K, V>, rhs: Subtree<K, V, A>) -> Grown<K, V, A> {
+        let (node, height) = rhs;
+        if height == lhs.1 {
+            return Grown::Split(lhs.0, item, node);
+        }
+        match node.into_node() {
             Node::Empty => unreachable!(),
             Node::Two(two) => {
                 let Two {
                     size: _,
-                    item,
-                    lhs,
+                    summary: _,
+                    item: item1,
+                    lhs: first,
                     rhs,
                 } = two;
-                (Node::three(orig_item, item, orig_lhs, lhs, rhs), true)
+                match Node::join_left(lhs, item, (first, height - 1)) {
+                    Grown::Fits(first) => Grown::Fits(Node::two(item1, first, rhs)),
+                    Grown::Split(a, x, b) => Grown::Fits(Node::three(x, item1, a, b, rhs)),
+                }
             }
             Node::Three(three) => {
                 let Three {
                     size: _,
+                    summary: _,
                     item1,
                     item2,
-                    lhs,
+                    lhs: first,
                     mid,
                     rhs,
                 } = three;
-                let node1 = Node::two(orig_item, orig_lhs, lhs);
-                let node2 = Node::two(item2, mid, rhs);
-                (Node::two(item1, node1, node2), false)
+                match Node::join_left(lhs, item, (first, height - 1)) {
+                    Grown::Fits(first) => Grown::Fits(Node::three(item1, item2, first, mid, rhs)),
+                    Grown::Split(a, x, b) => {
+                        Grown::Fits(Node::four(x, item1, item2, a, b, mid, rhs))
+                    }
+                }
             }
             Node::Four(four) => {
                 let Four {
                     size: _,
+                    summary: _,
                     item1,
                     item2,
                     item3,
-                    lhs,
+                    lhs: first,
                     lhs_mid,
                     rhs_mid,
                     rhs,
                 } = four;
-                let node1 = Node::three(orig_item, item1, orig_lhs, lhs, lhs_mid);
-                let node2 = Node::two(item3, rhs_mid, rhs);
-                (Node::two(item2, node1, node2), false)
+                match Node::join_left(lhs, item, (first, height - 1)) {
+                    Grown::Fits(first) => Grown::Fits(Node::four(
+                        item1, item2, item3, first, lhs_mid, rhs_mid, rhs,
+                    )),
+                    Grown::Split(a, x, b) => Grown::Split(
+                        Node::three(x, item1, a, b, lhs_mid),
+                        item2,
+                        Node::two(item3, rhs_mid, rhs),
+                    ),
+                }
             }
         }
     }
 
-    fn fix2_rhs(
-        orig_item: Item<K, V>,
-        orig_lhs: NodeBox<K, V>,
-        orig_rhs: NodeBox<K, V>,
-    ) -> (NodeBox<K, V>, bool) {
-        match *orig_lhs {
-            Node::Empty => unreachable!(),
+    fn below<Q: Ord + ?Sized>(key: &K, lower: Bound<&Q>) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        match lower {
+            Bound::Included(bound) => key.borrow() < bound,
+            Bound::Excluded(bound) => key.borrow() <= bound,
+            Bound::Unbounded => false,
+        }
+    }
+
+    fn above<Q: Ord + ?Sized>(key: &K, upper: Bound<&Q>) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        match upper {
+            Bound::Included(bound) => key.borrow() > bound,
+            Bound::Excluded(bound) => key.borrow() >= bound,
+            Bound::Unbounded => false,
+        }
+    }
+
+    // Split a subtree into the items that fall below the bound and the rest.
+    fn split<Q: Ord + ?Sized>(
+        subtree: Subtree<K, V, A>,
+        lower: Bound<&Q>,
+    ) -> (Subtree<K, V, A>, Subtree<K, V, A>)
+    where
+        K: Borrow<Q>,
+    {
+        let (node, height) = subtree;
+        let h = height.saturating_sub(1);
+        match node.into_node() {
+            Node::Empty => ((Node::empty(), 0), (Node::empty(), 0)),
             Node::Two(two) => {
                 let Two {
                     size: _,
+                    summary: _,
                     item,
                     lhs,
                     rhs,
                 } = two;
-                (Node::three(item, orig_item, lhs, rhs, orig_rhs), true)
+                if !Self::below(&item.0, lower) {
+                    let (l, r) = Node::split((lhs, h), lower);
+                    (l, Node::join(r, item, (rhs, h)))
+                } else {
+                    let (l, r) = Node::split((rhs, h), lower);
+                    (Node::join((lhs, h), item, l), r)
+                }
             }
             Node::Three(three) => {
                 let Three {
                     size: _,
+                    summary: _,
                     item1,
                     item2,
                     lhs,
                     mid,
                     rhs,
                 } = three;
-                let node1 = Node::two(item1, lhs, mid);
-                let node2 = Node::two(orig_item, rhs, orig_rhs);
-                (Node::two(item2, node1, node2), false)
+                if !Self::below(&item1.0, lower) {
+                    let (l, r) = Node::split((lhs, h), lower);
+                    let rest = (Node::two(item2, mid, rhs), height);
+                    (l, Node::join(r, item1, rest))
+                } else if !Self::below(&item2.0, lower) {
+                    let (l, r) = Node::split((mid, h), lower);
+                    (
+                        Node::join((lhs, h), item1, l),
+                        Node::join(r, item2, (rhs, h)),
+                    )
+                } else {
+                    let (l, r) = Node::split((rhs, h), lower);
+                    let rest = (Node::two(item1, lhs, mid), height);
+                    (Node::join(rest, item2, l), r)
+                }
             }
             Node::Four(four) => {
                 let Four {
                     size: _,
+                    summary: _,
                     item1,
                     item2,
                     item3,
@@ -933,51 +1191,62 @@ impl<K: Eq + Ord, V> Node<K, V> {
                     rhs_mid,
                     rhs,
                 } = four;
-                let node1 = Node::three(item1, item2, lhs, lhs_mid, rhs_mid);
-                let node2 = Node::two(orig_item, rhs, orig_rhs);
-                (Node::two(item3, node1, node2), false)
+                if !Self::below(&item1.0, lower) {
+                    let (l, r) = Node::split((lhs, h), lower);
+                    let rest = (Node::three(item2, item3, lhs_mid, rhs_mid, rhs), height);
+                    (l, Node::join(r, item1, rest))
+                } else if !Self::below(&item2.0, lower) {
+                    let (l, r) = Node::split((lhs_mid, h), lower);
+                    let rest = (Node::two(item3, rhs_mid, rhs), height);
+                    (Node::join((lhs, h), item1, l), Node::join(r, item2, rest))
+                } else if !Self::below(&item3.0, lower) {
+                    let (l, r) = Node::split((rhs_mid, h), lower);
+                    let rest = (Node::two(item1, lhs, lhs_mid), height);
+                    (Node::join(rest, item2, l), Node::join(r, item3, (rhs, h)))
+                } else {
+                    let (l, r) = Node::split((rhs, h), lower);
+                    let rest = (Node::three(item1, item2, lhs, lhs_mid, rhs_mid), height);
+                    (Node::join(rest, item3, l), r)
+                }
             }
         }
     }
 
-    fn fix3_lhs(
-        orig_item1: Item<K, V>,
-        orig_item2: Item<K, V>,
-        orig_lhs: NodeBox<K, V>,
-        orig_mid: NodeBox<K, V>,
-        orig_rhs: NodeBox<K, V>,
-    ) -> (NodeBox<K, V>, bool) {
-        match *orig_mid {
-            Node::Empty => unreachable!(),
+    pub fn visit<Visitor: FnMut(&Item<K, V>)>(&self, visitor: &mut Visitor) {
+        match self {
+            Node::Empty => {}
             Node::Two(two) => {
                 let Two {
                     size: _,
+                    summary: _,
                     item,
                     lhs,
                     rhs,
                 } = two;
-                let node = Node::three(orig_item1, item, orig_lhs, lhs, rhs);
-                (Node::two(orig_item2, node, orig_rhs), false)
+                lhs.visit(visitor);
+                visitor(item);
+                rhs.visit(visitor);
             }
             Node::Three(three) => {
                 let Three {
                     size: _,
+                    summary: _,
                     item1,
                     item2,
                     lhs,
                     mid,
                     rhs,
                 } = three;
-                let node1 = Node::two(orig_item1, orig_lhs, lhs);
-                let node2 = Node::two(item2, mid, rhs);
-                (
-                    Node::three(item1, orig_item2, node1, node2, orig_rhs),
-                    false,
-                )
+                lhs.visit(visitor);
+                visitor(item1);
+                mid.visit(visitor);
+                visitor(item2);
+                rhs.visit(visitor);
             }
             Node::Four(four) => {
                 let Four {
                     size: _,
+                    summary: _,
                     item1,
                     item2,
                     item3,
@@ -986,54 +1255,52 @@ impl<K: Eq + Ord, V> Node<K, V> {
                     rhs_mid,
                     rhs,
                 } = four;
-                let node1 = Node::two(orig_item1, orig_lhs, lhs);
-                let node2 = Node::three(item2, item3, lhs_mid, rhs_mid, rhs);
-                (
-                    Node::three(item1, orig_item2, node1, node2, orig_rhs),
-                    false,
-                )
+                lhs.visit(visitor);
+                visitor(item1);
+                lhs_mid.visit(visitor);
+                visitor(item2);
+                rhs_mid.visit(visitor);
+                visitor(item3);
+                rhs.visit(visitor);
             }
         }
     }
 
-    fn fix3_mid(
-        orig_item1: Item<K, V>,
-        orig_item2: Item<K, V>,
-        orig_lhs: NodeBox<K, V>,
-        orig_mid: NodeBox<K, V>,
-        orig_rhs: NodeBox<K, V>,
-    ) -> (NodeBox<K, V>, bool) {
-        match *orig_lhs {
-            Node::Empty => unreachable!(),
+    pub fn visit_mut<Visitor: FnMut(&K, &mut V)>(&mut self, visitor: &mut Visitor) {
+        match self {
+            Node::Empty => {}
             Node::Two(two) => {
                 let Two {
                     size: _,
+                    summary: _,
                     item,
                     lhs,
                     rhs,
                 } = two;
-                let node = Node::three(item, orig_item1, lhs, rhs, orig_mid);
-                (Node::two(orig_item2, node, orig_rhs), false)
+                lhs.visit_mut(visitor);
+                visitor(&item.0, &mut item.1);
+                rhs.visit_mut(visitor);
             }
             Node::Three(three) => {
                 let Three {
                     size: _,
+                    summary: _,
                     item1,
                     item2,
                     lhs,
                     mid,
                     rhs,
                 } = three;
-                let node1 = Node::two(item1, lhs, mid);
-                let node2 = Node::two(orig_item1, rhs, orig_mid);
-                (
-                    Node::three(item2, orig_item2, node1, node2, orig_rhs),
-                    false,
-                )
+                lhs.visit_mut(visitor);
+                visitor(&item1.0, &mut item1.1);
+                mid.visit_mut(visitor);
+                visitor(&item2.0, &mut item2.1);
+                rhs.visit_mut(visitor);
             }
             Node::Four(four) => {
                 let Four {
                     size: _,
+                    summary: _,
                     item1,
                     item2,
                     item3,
@@ -1042,51 +1309,263 @@ impl<K: Eq + Ord, V> Node<K, V> {
                     rhs_mid,
                     rhs,
                 } = four;
-                let node1 = Node::three(item1, item2, lhs, lhs_mid, rhs_mid);
-                let node2 = Node::two(orig_item1, rhs, orig_mid);
-                (Node::two(item3, node1, node2), false)
+                lhs.visit_mut(visitor);
+                visitor(&item1.0, &mut item1.1);
+                lhs_mid.visit_mut(visitor);
+                visitor(&item2.0, &mut item2.1);
+                rhs_mid.visit_mut(visitor);
+                visitor(&item3.0, &mut item3.1);
+                rhs.visit_mut(visitor);
+            }
+        }
+    }
+}
+
+// Only for unaugmented trees, where counting an item into a node above it
+// leaves nothing else to recompute.
+impl<K: Eq + Ord, V> Node<K, V> {
+    // Look up `key` for an entry. Full nodes are split on the way down
+    // rather than on the way back up, so if the key is absent, the leaf it
+    // belongs in has room for it and no node above needs restructuring.
+    // Gives the item, or else the depth of that leaf and the index to insert
+    // at, with the child taken at each level above it recorded in `children`.
+    fn search_vacant<'a>(
+        root: &'a mut NodeBox<K, V>,
+        key: &K,
+        children: &mut [u8; MAX_DEPTH],
+    ) -> Result<&'a mut Item<K, V>, (usize, usize)> {
+        if root.len() == 3 {
+            let (item, upper) = Node::halve(root);
+            let lhs = std::mem::replace(root, Node::empty());
+            *root = Node::two(item, lhs, upper);
+        }
+        let mut link = root;
+        let mut depth = 0;
+        loop {
+            let i = match link.search(key) {
+                Ok(i) => return Ok(link.as_mut().unwrap().item_mut(i)),
+                Err(i) => i,
+            };
+            if link.is_empty() || link.child(i).is_empty() {
+                return Err((depth, i));
             }
+            if link.child(i).len() == 3 {
+                let (item, upper) = Node::halve(link.as_mut().unwrap().child_mut(i));
+                let mut parts = Parts::take(link);
+                parts.insert(i, item, i + 1, upper);
+                link.set(parts.into_node());
+                continue;
+            }
+            children[depth] = i as u8;
+            depth += 1;
+            link = link.as_mut().unwrap().child_mut(i);
+        }
+    }
+
+    // Insert at the leaf found by `search_vacant`, following the recorded
+    // children back down to it without comparing keys, and counting the item
+    // into each node on the way.
+    fn insert_vacant<'a>(
+        root: &'a mut NodeBox<K, V>,
+        children: &[u8],
+        i: usize,
+        item: Item<K, V>,
+    ) -> &'a mut Item<K, V> {
+        let mut link = root;
+        for &child in children {
+            let node = link.as_mut().unwrap();
+            node.grow();
+            link = node.child_mut(child as usize);
+        }
+        if link.is_empty() {
+            *link = Node::two(item, Node::empty(), Node::empty());
+        } else {
+            let mut parts = Parts::take(link);
+            parts.insert(i, item, i + 1, Node::empty());
+            link.set(parts.into_node());
+        }
+        link.as_mut().unwrap().item_mut(i)
+    }
+
+    // Split a full node about its middle item, keeping the lower half in
+    // place and handing back the middle item and the upper half.
+    fn halve(node: &mut NodeBox<K, V>) -> Grafted<K, V, ()> {
+        let Parts {
+            len: _,
+            items: [item1, item2, item3, _],
+            children: [lhs, lhs_mid, rhs_mid, rhs, _],
+        } = Parts::take(node);
+        node.set(Node::new2(item1.unwrap(), lhs.unwrap(), lhs_mid.unwrap()));
+        let upper = Node::two(item3.unwrap(), rhs_mid.unwrap(), rhs.unwrap());
+        (item2.unwrap(), upper)
+    }
+
+    fn grow(&mut self) {
+        match self {
+            Node::Empty => unreachable!(),
+            Node::Two(two) => two.size += 1,
+            Node::Three(three) => three.size += 1,
+            Node::Four(four) => four.size += 1,
+        }
+    }
+}
+
+// A link to a subtree. Empty subtrees take no allocation, but still read as
+// `Node::Empty` through `Deref`; changes go through `as_mut` and `set`.
+#[derive(Clone)]
+struct NodeBox<K: Eq + Ord, V, A: Augment<K, V> = ()>(Option<Box<Node<K, V, A>>>);
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> NodeBox<K, V, A> {
+    fn into_node(self) -> Node<K, V, A> {
+        self.0.map_or(Node::Empty, |node| *node)
+    }
+
+    fn as_mut(&mut self) -> Option<&mut Node<K, V, A>> {
+        self.0.as_deref_mut()
+    }
+
+    // Replace the subtree, reusing the existing allocation if there is one.
+    fn set(&mut self, node: Node<K, V, A>) {
+        match (&mut self.0, node) {
+            (_, Node::Empty) => self.0 = None,
+            (Some(current), node) => **current = node,
+            (None, node) => self.0 = Some(Box::new(node)),
+        }
+    }
+
+    fn nth_mut(&mut self, index: usize) -> Option<&mut Item<K, V>> {
+        self.as_mut()?.nth_mut(index)
+    }
+
+    fn locate_mut<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<&mut Item<K, V>>
+    where
+        K: Borrow<Q>,
+    {
+        self.as_mut()?.locate_mut(key)
+    }
+
+    fn visit_mut<Visitor: FnMut(&K, &mut V)>(&mut self, visitor: &mut Visitor) {
+        if let Some(node) = self.as_mut() {
+            node.visit_mut(visitor);
+        }
+    }
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> std::ops::Deref for NodeBox<K, V, A> {
+    type Target = Node<K, V, A>;
+
+    fn deref(&self) -> &Node<K, V, A> {
+        match &self.0 {
+            None => &Node::Empty,
+            Some(node) => node,
         }
     }
+}
+
+// A node together with its height.
+type Subtree<K, V, A> = (NodeBox<K, V, A>, usize);
+
+// The result of grafting onto the side of a node: either a node of the same
+// height, or two such nodes and the item that separates them, which the
+// caller must absorb one level up.
+enum Grown<K: Eq + Ord, V, A: Augment<K, V> = ()> {
+    Fits(NodeBox<K, V, A>),
+    Split(NodeBox<K, V, A>, Item<K, V>, NodeBox<K, V, A>),
+}
+
+// An item and the subtree to its right, split off a full node for its parent
+// to absorb.
+type Grafted<K, V, A> = (Item<K, V>, NodeBox<K, V, A>);
+
+// What a removal does at a node on its way down.
+enum Step {
+    Take(usize),
+    Descend(usize),
+}
+
+// Trees of height h hold at least 2^h - 1 items, so no tree whose size fits
+// in a usize can be deeper than this.
+pub(crate) const MAX_DEPTH: usize = usize::BITS as usize;
+
+// The links visited on the way down from the root, and the child taken from
+// each, so that a mutation can be finished bottom-up without recursion. Each
+// link points into the node behind the one before it, so once the descent is
+// over they are dereferenced one at a time, deepest first.
+struct Path<K: Eq + Ord, V, A: Augment<K, V>> {
+    links: [*mut NodeBox<K, V, A>; MAX_DEPTH],
+    children: [usize; MAX_DEPTH],
+    len: usize,
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> Path<K, V, A> {
+    fn new() -> Path<K, V, A> {
+        Path {
+            links: [std::ptr::null_mut(); MAX_DEPTH],
+            children: [0; MAX_DEPTH],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, link: *mut NodeBox<K, V, A>, child: usize) {
+        self.links[self.len] = link;
+        self.children[self.len] = child;
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<(*mut NodeBox<K, V, A>, usize)> {
+        self.len = self.len.checked_sub(1)?;
+        Some((self.links[self.len], self.children[self.len]))
+    }
+}
+
+// The items and children of a node, taken out of it so that they can be
+// shuffled and put back without reallocating the node.
+struct Parts<K: Eq + Ord, V, A: Augment<K, V> = ()> {
+    len: usize,
+    items: [Option<Item<K, V>>; 4],
+    children: [Option<NodeBox<K, V, A>>; 5],
+}
 
-    fn fix3_rhs(
-        orig_item1: Item<K, V>,
-        orig_item2: Item<K, V>,
-        orig_lhs: NodeBox<K, V>,
-        orig_mid: NodeBox<K, V>,
-        orig_rhs: NodeBox<K, V>,
-    ) -> (NodeBox<K, V>, bool) {
-        match *orig_mid {
+impl<K: Eq + Ord, V, A: Augment<K, V>> Parts<K, V, A> {
+    // Take the node apart, leaving its allocation in place to be refilled.
+    fn take(node: &mut NodeBox<K, V, A>) -> Parts<K, V, A> {
+        let node = node.as_mut().expect("cannot take apart an empty node");
+        match std::mem::replace(node, Node::Empty) {
             Node::Empty => unreachable!(),
             Node::Two(two) => {
                 let Two {
                     size: _,
+                    summary: _,
                     item,
                     lhs,
                     rhs,
                 } = two;
-                let node = Node::three(item, orig_item2, lhs, rhs, orig_rhs);
-                (Node::two(orig_item1, orig_lhs, node), false)
+                Parts {
+                    len: 1,
+                    items: [Some(item), None, None, None],
+                    children: [Some(lhs), Some(rhs), None, None, None],
+                }
             }
             Node::Three(three) => {
                 let Three {
                     size: _,
+                    summary: _,
                     item1,
                     item2,
                     lhs,
                     mid,
                     rhs,
                 } = three;
-                let node1 = Node::two(item1, lhs, mid);
-                let node2 = Node::two(orig_item2, rhs, orig_rhs);
-                (
-                    Node::three(orig_item1, item2, orig_lhs, node1, node2),
-                    false,
-                )
+                Parts {
+                    len: 2,
+                    items: [Some(item1), Some(item2), None, None],
+                    children: [Some(lhs), Some(mid), Some(rhs), None, None],
+                }
             }
             Node::Four(four) => {
                 let Four {
                     size: _,
+                    summary: _,
                     item1,
                     item2,
                     item3,
@@ -1095,350 +1574,188 @@ impl<K: Eq + Ord, V> Node<K, V> {
                     rhs_mid,
                     rhs,
                 } = four;
-                let node1 = Node::three(item1, item2, lhs, lhs_mid, rhs_mid);
-                let node2 = Node::two(orig_item2, rhs, orig_rhs);
-                (
-                    Node::three(orig_item1, item3, orig_lhs, node1, node2),
-                    false,
-                )
-            }
-        }
-    }
-
-    fn fix4_lhs(
-        orig_item1: Item<K, V>,
-        orig_item2: Item<K, V>,
-        orig_item3: Item<K, V>,
-        orig_lhs: NodeBox<K, V>,
-        orig_lhs_mid: NodeBox<K, V>,
-        orig_rhs_mid: NodeBox<K, V>,
-        orig_rhs: NodeBox<K, V>,
-    ) -> (NodeBox<K, V>, bool) {
-        match *orig_lhs_mid {
-            Node::Empty => unreachable!(),
-            Node::Two(two) => {
-                let Two {
-                    size: _,
-                    item,
-                    lhs,
-                    rhs,
-                } = two;
-                let node = Node::three(orig_item1, item, orig_lhs, lhs, rhs);
-                (
-                    Node::three(orig_item2, orig_item3, node, orig_rhs_mid, orig_rhs),
-                    false,
-                )
-            }
-            Node::Three(three) => {
-                let Three {
-                    size: _,
-                    item1,
-                    item2,
-                    lhs,
-                    mid,
-                    rhs,
-                } = three;
-                let node1 = Node::two(orig_item1, orig_lhs, lhs);
-                let node2 = Node::two(item2, mid, rhs);
-                (
-                    Node::four(
-                        item1,
-                        orig_item2,
-                        orig_item3,
-                        node1,
-                        node2,
-                        orig_rhs_mid,
-                        orig_rhs,
-                    ),
-                    false,
-                )
-            }
-            Node::Four(four) => {
-                let Four {
-                    size: _,
-                    item1,
-                    item2,
-                    item3,
-                    lhs,
-                    lhs_mid,
-                    rhs_mid,
-                    rhs,
-                } = four;
-                let node1 = Node::two(orig_item1, orig_lhs, lhs);
-                let node2 = Node::three(item2, item3, lhs_mid, rhs_mid, rhs);
-                (
-                    Node::four(
-                        item1,
-                        orig_item2,
-                        orig_item3,
-                        node1,
-                        node2,
-                        orig_rhs_mid,
-                        orig_rhs,
-                    ),
-                    false,
-                )
+                Parts {
+                    len: 3,
+                    items: [Some(item1), Some(item2), Some(item3), None],
+                    children: [Some(lhs), Some(lhs_mid), Some(rhs_mid), Some(rhs), None],
+                }
             }
         }
     }
 
-    fn fix4_lhs_mid(
-        orig_item1: Item<K, V>,
-        orig_item2: Item<K, V>,
-        orig_item3: Item<K, V>,
-        orig_lhs: NodeBox<K, V>,
-        orig_lhs_mid: NodeBox<K, V>,
-        orig_rhs_mid: NodeBox<K, V>,
-        orig_rhs: NodeBox<K, V>,
-    ) -> (NodeBox<K, V>, bool) {
-        match *orig_rhs_mid {
-            Node::Empty => unreachable!(),
-            Node::Two(two) => {
-                let Two {
-                    size: _,
-                    item,
-                    lhs,
-                    rhs,
-                } = two;
-                let node = Node::three(orig_item2, item, orig_lhs_mid, lhs, rhs);
-                (
-                    Node::three(orig_item1, orig_item3, orig_lhs, node, orig_rhs),
-                    false,
-                )
-            }
-            Node::Three(three) => {
-                let Three {
-                    size: _,
-                    item1,
-                    item2,
-                    lhs,
-                    mid,
-                    rhs,
-                } = three;
-                let node1 = Node::two(orig_item2, orig_lhs_mid, lhs);
-                let node2 = Node::two(item2, mid, rhs);
-                (
-                    Node::four(
-                        orig_item1, item1, orig_item3, orig_lhs, node1, node2, orig_rhs,
-                    ),
-                    false,
-                )
-            }
-            Node::Four(four) => {
-                let Four {
-                    size: _,
-                    item1,
-                    item2,
-                    item3,
-                    lhs,
-                    lhs_mid,
-                    rhs_mid,
-                    rhs,
-                } = four;
-                let node1 = Node::two(orig_item2, orig_lhs_mid, lhs);
-                let node2 = Node::three(item2, item3, lhs_mid, rhs_mid, rhs);
-                (
-                    Node::four(
-                        orig_item1, item1, orig_item3, orig_lhs, node1, node2, orig_rhs,
-                    ),
-                    false,
-                )
-            }
-        }
+    // Insert an item at index `i` and a child at index `j`.
+    fn insert(&mut self, i: usize, item: Item<K, V>, j: usize, child: NodeBox<K, V, A>) {
+        self.items[self.len] = Some(item);
+        self.items[i..=self.len].rotate_right(1);
+        self.children[self.len + 1] = Some(child);
+        self.children[j..=self.len + 1].rotate_right(1);
+        self.len += 1;
     }
 
-    fn fix4_rhs_mid(
-        orig_item1: Item<K, V>,
-        orig_item2: Item<K, V>,
-        orig_item3: Item<K, V>,
-        orig_lhs: NodeBox<K, V>,
-        orig_lhs_mid: NodeBox<K, V>,
-        orig_rhs_mid: NodeBox<K, V>,
-        orig_rhs: NodeBox<K, V>,
-    ) -> (NodeBox<K, V>, bool) {
-        match *orig_rhs {
-            Node::Empty => unreachable!(),
-            Node::Two(two) => {
-                let Two {
-                    size: _,
-                    item,
-                    lhs,
-                    rhs,
-                } = two;
-                let node = Node::three(orig_item3, item, orig_rhs_mid, lhs, rhs);
-                (
-                    Node::three(orig_item1, orig_item2, orig_lhs, orig_lhs_mid, node),
-                    false,
-                )
-            }
-            Node::Three(three) => {
-                let Three {
-                    size: _,
-                    item1,
-                    item2,
-                    lhs,
-                    mid,
-                    rhs,
-                } = three;
-                let node1 = Node::two(orig_item3, orig_rhs_mid, lhs);
-                let node2 = Node::two(item2, mid, rhs);
-                (
-                    Node::four(
-                        orig_item1,
-                        orig_item2,
-                        item1,
-                        orig_lhs,
-                        orig_lhs_mid,
-                        node1,
-                        node2,
-                    ),
-                    false,
-                )
-            }
-            Node::Four(four) => {
-                let Four {
-                    size: _,
-                    item1,
-                    item2,
-                    item3,
-                    lhs,
-                    lhs_mid,
-                    rhs_mid,
-                    rhs,
-                } = four;
-                let node1 = Node::two(orig_item3, orig_rhs_mid, lhs);
-                let node2 = Node::three(item2, item3, lhs_mid, rhs_mid, rhs);
-                (
-                    Node::four(
-                        orig_item1,
-                        orig_item2,
-                        item1,
-                        orig_lhs,
-                        orig_lhs_mid,
-                        node1,
-                        node2,
-                    ),
-                    false,
-                )
-            }
-        }
+    // Remove the item at index `i` and the child at index `j`.
+    fn remove(&mut self, i: usize, j: usize) -> (Item<K, V>, NodeBox<K, V, A>) {
+        self.items[i..self.len].rotate_left(1);
+        self.children[j..=self.len].rotate_left(1);
+        self.len -= 1;
+        let item = self.items[self.len].take().unwrap();
+        let child = self.children[self.len + 1].take().unwrap();
+        (item, child)
     }
 
-    fn fix4_rhs(
-        orig_item1: Item<K, V>,
-        orig_item2: Item<K, V>,
-        orig_item3: Item<K, V>,
-        orig_lhs: NodeBox<K, V>,
-        orig_lhs_mid: NodeBox<K, V>,
-        orig_rhs_mid: NodeBox<K, V>,
-        orig_rhs: NodeBox<K, V>,
-    ) -> (NodeBox<K, V>, bool) {
-        match *orig_rhs_mid {
-            Node::Empty => unreachable!(),
-            Node::Two(two) => {
-                let Two {
-                    size: _,
-                    item,
-                    lhs,
-                    rhs,
-                } = two;
-                let node = Node::three(item, orig_item3, lhs, rhs, orig_rhs);
-                (
-                    Node::three(orig_item1, orig_item2, orig_lhs, orig_lhs_mid, node),
-                    false,
-                )
-            }
-            Node::Three(three) => {
-                let Three {
-                    size: _,
-                    item1,
-                    item2,
-                    lhs,
-                    mid,
-                    rhs,
-                } = three;
-                let node1 = Node::two(item1, lhs, mid);
-                let node2 = Node::two(orig_item3, rhs, orig_rhs);
-                (
-                    Node::four(
-                        orig_item1,
-                        orig_item2,
-                        item2,
-                        orig_lhs,
-                        orig_lhs_mid,
-                        node1,
-                        node2,
-                    ),
-                    false,
-                )
-            }
-            Node::Four(four) => {
-                let Four {
-                    size: _,
-                    item1,
-                    item2,
-                    item3,
-                    lhs,
-                    lhs_mid,
-                    rhs_mid,
-                    rhs,
-                } = four;
-                let node1 = Node::three(item1, item2, lhs, lhs_mid, rhs_mid);
-                let node2 = Node::two(orig_item3, rhs, orig_rhs);
-                (
-                    Node::four(
-                        orig_item1,
-                        orig_item2,
-                        item3,
-                        orig_lhs,
-                        orig_lhs_mid,
-                        node1,
-                        node2,
-                    ),
-                    false,
-                )
-            }
+    fn into_node(self) -> Node<K, V, A> {
+        let Parts {
+            len,
+            items: [item1, item2, item3, _],
+            children: [lhs, lhs_mid, rhs_mid, rhs, _],
+        } = self;
+        match len {
+            1 => Node::new2(item1.unwrap(), lhs.unwrap(), lhs_mid.unwrap()),
+            2 => Node::new3(
+                item1.unwrap(),
+                item2.unwrap(),
+                lhs.unwrap(),
+                lhs_mid.unwrap(),
+                rhs_mid.unwrap(),
+            ),
+            3 => Node::new4(
+                item1.unwrap(),
+                item2.unwrap(),
+                item3.unwrap(),
+                lhs.unwrap(),
+                lhs_mid.unwrap(),
+                rhs_mid.unwrap(),
+                rhs.unwrap(),
+            ),
+            _ => panic!("node has no room for {} items", len),
         }
     }
 }
 
-type NodeBox<K, V> = Box<Node<K, V>>;
+// What a removal descends towards: either a key, or a position in key order.
+trait Target<K> {
+    // Compare the target with an item that is preceded by `before` items
+    // within the subtree currently being searched.
+    fn compare(&self, key: &K, before: usize) -> std::cmp::Ordering;
+
+    // Step into a child subtree that is preceded by `before` items within
+    // the subtree currently being searched.
+    fn descend(&mut self, before: usize);
+}
+
+impl<K: Borrow<Q>, Q: Ord + ?Sized> Target<K> for &Q {
+    fn compare(&self, key: &K, _before: usize) -> std::cmp::Ordering {
+        (*self).cmp(key.borrow())
+    }
+
+    fn descend(&mut self, _before: usize) {}
+}
+
+struct Position(usize);
+
+impl<K> Target<K> for Position {
+    fn compare(&self, _key: &K, before: usize) -> std::cmp::Ordering {
+        self.0.cmp(&before)
+    }
+
+    fn descend(&mut self, before: usize) {
+        self.0 -= before;
+    }
+}
 
-struct Two<K: Eq + Ord, V> {
+#[derive(Clone)]
+struct Two<K: Eq + Ord, V, A: Augment<K, V> = ()> {
     size: usize,
+    summary: A,
     item: Item<K, V>,
-    lhs: NodeBox<K, V>,
-    rhs: NodeBox<K, V>,
+    lhs: NodeBox<K, V, A>,
+    rhs: NodeBox<K, V, A>,
 }
 
-struct Three<K: Eq + Ord, V> {
+#[derive(Clone)]
+struct Three<K: Eq + Ord, V, A: Augment<K, V> = ()> {
     size: usize,
+    summary: A,
     item1: Item<K, V>,
     item2: Item<K, V>,
-    lhs: NodeBox<K, V>,
-    mid: NodeBox<K, V>,
-    rhs: NodeBox<K, V>,
+    lhs: NodeBox<K, V, A>,
+    mid: NodeBox<K, V, A>,
+    rhs: NodeBox<K, V, A>,
 }
 
-struct Four<K: Eq + Ord, V> {
+#[derive(Clone)]
+struct Four<K: Eq + Ord, V, A: Augment<K, V> = ()> {
     size: usize,
+    summary: A,
     item1: Item<K, V>,
     item2: Item<K, V>,
     item3: Item<K, V>,
-    lhs: NodeBox<K, V>,
-    lhs_mid: NodeBox<K, V>,
-    rhs_mid: NodeBox<K, V>,
-    rhs: NodeBox<K, V>,
+    lhs: NodeBox<K, V, A>,
+    lhs_mid: NodeBox<K, V, A>,
+    rhs_mid: NodeBox<K, V, A>,
+    rhs: NodeBox<K, V, A>,
 }
 
-pub struct Tree234<K: Eq + Ord, V> {
-    root: NodeBox<K, V>,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildError {
+    Unsorted { index: usize },
+    Duplicate { index: usize },
 }
 
-impl<K: Eq + Ord, V> Tree234<K, V> {
-    pub fn new() -> Tree234<K, V> {
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildError::Unsorted { index } => {
+                write!(f, "key at index {} is less than the key before it", index)
+            }
+            BuildError::Duplicate { index } => {
+                write!(f, "key at index {} is equal to the key before it", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+#[derive(Clone)]
+pub struct Tree234<K: Eq + Ord, V, A: Augment<K, V> = ()> {
+    root: NodeBox<K, V, A>,
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> Tree234<K, V, A> {
+    // Reports the first item out of order, if any.
+    fn check_sorted(items: &[Item<K, V>]) -> Result<(), BuildError> {
+        for (i, pair) in items.windows(2).enumerate() {
+            match pair[0].0.cmp(&pair[1].0) {
+                std::cmp::Ordering::Less => {}
+                std::cmp::Ordering::Equal => return Err(BuildError::Duplicate { index: i + 1 }),
+                std::cmp::Ordering::Greater => return Err(BuildError::Unsorted { index: i + 1 }),
+            }
+        }
+        Ok(())
+    }
+
+    // Later items replace earlier ones with the same key.
+    fn from_unsorted(mut items: Vec<Item<K, V>>) -> Tree234<K, V, A> {
+        items.sort_by(|a, b| a.0.cmp(&b.0));
+        items.dedup_by(|next, prev| {
+            let duplicate = next.0 == prev.0;
+            if duplicate {
+                std::mem::swap(next, prev);
+            }
+            duplicate
+        });
+        Tree234::from_strictly_sorted(items)
+    }
+
+    // Use the smallest height that can hold every item.
+    fn from_strictly_sorted(items: Vec<Item<K, V>>) -> Tree234<K, V, A> {
+        let count = items.len();
+        let mut height = 0;
+        while 4usize.saturating_pow(height as u32) - 1 < count {
+            height += 1;
+        }
         Tree234 {
-            root: Node::empty(),
+            root: Node::build(&mut items.into_iter(), count, height),
         }
     }
 
@@ -1446,24 +1763,251 @@ impl<K: Eq + Ord, V> Tree234<K, V> {
         self.root.size()
     }
 
-    pub fn get(&self, key: &K) -> Option<&Item<K, V>> {
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&Item<K, V>>
+    where
+        K: Borrow<Q>,
+    {
         self.root.get(key)
     }
 
+    pub fn nth(&self, index: usize) -> Option<&Item<K, V>> {
+        self.root.nth(index)
+    }
+
+    pub fn rank<Q: Ord + ?Sized>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        self.root.rank(key)
+    }
+
+    pub fn first(&self) -> Option<&Item<K, V>> {
+        self.root.first()
+    }
+
+    pub fn last(&self) -> Option<&Item<K, V>> {
+        self.root.last()
+    }
+
+    pub fn floor<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&Item<K, V>>
+    where
+        K: Borrow<Q>,
+    {
+        self.root.lower_neighbour(key, true)
+    }
+
+    pub fn ceiling<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&Item<K, V>>
+    where
+        K: Borrow<Q>,
+    {
+        self.root.upper_neighbour(key, true)
+    }
+
+    pub fn predecessor<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&Item<K, V>>
+    where
+        K: Borrow<Q>,
+    {
+        self.root.lower_neighbour(key, false)
+    }
+
+    pub fn successor<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&Item<K, V>>
+    where
+        K: Borrow<Q>,
+    {
+        self.root.upper_neighbour(key, false)
+    }
+
     pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        Node::insert(&mut self.root, key, value)
+    }
+
+    pub fn remove<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        self.remove_entry(key).map(|item| item.1)
+    }
+
+    pub fn remove_entry<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<Item<K, V>>
+    where
+        K: Borrow<Q>,
+    {
+        Node::remove(&mut self.root, &mut { key })
+    }
+
+    fn remove_nth(&mut self, index: usize) -> Option<Item<K, V>> {
+        if index >= self.size() {
+            return None;
+        }
+        Node::remove(&mut self.root, &mut Position(index))
+    }
+
+    pub fn contains_key<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.get(key).is_some()
+    }
+
+    pub fn pop_first(&mut self) -> Option<Item<K, V>> {
+        Node::remove_first(&mut self.root)
+    }
+
+    pub fn pop_last(&mut self) -> Option<Item<K, V>> {
+        Node::remove_last(&mut self.root)
+    }
+
+    pub fn split_off<Q: Ord + ?Sized>(&mut self, key: &Q) -> Tree234<K, V, A>
+    where
+        K: Borrow<Q>,
+    {
         let mut root = Node::empty();
         std::mem::swap(&mut self.root, &mut root);
-        let (root, replaced) = root.insert(key, value);
-        self.root = root;
-        replaced
+        let height = root.height();
+        let ((lhs, _), (rhs, _)) = Node::split((root, height), Bound::Included(key));
+        self.root = lhs;
+        Tree234 { root: rhs }
     }
 
-    pub fn remove(&mut self, key: &K) -> Option<V> {
+    // Every key in `other` must be greater than every key in `self`.
+    pub fn append_ordered(&mut self, mut other: Tree234<K, V, A>) {
+        if let (Some(last), Some(first)) = (self.last(), other.first()) {
+            assert!(last.0 < first.0, "trees overlap");
+        }
+        let Some(item) = other.pop_first() else {
+            return;
+        };
         let mut root = Node::empty();
         std::mem::swap(&mut self.root, &mut root);
-        let (root, result, _reduced) = root.remove(key);
+        let lhs_height = root.height();
+        let rhs_height = other.root.height();
+        let (root, _) = Node::join((root, lhs_height), item, (other.root, rhs_height));
         self.root = root;
-        result
+    }
+
+    pub fn join(mut self, other: Tree234<K, V, A>) -> Tree234<K, V, A> {
+        self.append_ordered(other);
+        self
+    }
+
+    pub fn remove_range<Q: Ord + ?Sized, R: RangeBounds<Q>>(&mut self, range: R)
+    where
+        K: Borrow<Q>,
+    {
+        self.detach_range(range.start_bound(), range.end_bound());
+    }
+
+    pub fn drain_range<Q: Ord + ?Sized, R: RangeBounds<Q>>(&mut self, range: R) -> IntoIter<K, V, A>
+    where
+        K: Borrow<Q>,
+    {
+        self.detach_range(range.start_bound(), range.end_bound())
+            .into_iter()
+    }
+
+    // Split the tree either side of the range and join the outer parts back
+    // together, returning the inner one.
+    fn detach_range<Q: Ord + ?Sized>(
+        &mut self,
+        lower: Bound<&Q>,
+        upper: Bound<&Q>,
+    ) -> Tree234<K, V, A>
+    where
+        K: Borrow<Q>,
+    {
+        let mut root = Node::empty();
+        std::mem::swap(&mut self.root, &mut root);
+        let height = root.height();
+        let (lhs, rest) = Node::split((root, height), lower);
+        let above = match upper {
+            Bound::Included(key) => Bound::Excluded(key),
+            Bound::Excluded(key) => Bound::Included(key),
+            Bound::Unbounded => {
+                self.root = lhs.0;
+                return Tree234 { root: rest.0 };
+            }
+        };
+        let (mid, rhs) = Node::split(rest, above);
+        self.root = lhs.0;
+        self.append_ordered(Tree234 { root: rhs.0 });
+        Tree234 { root: mid.0 }
+    }
+
+    pub fn union_with<F: FnMut(&K, V, V) -> V>(
+        self,
+        other: Tree234<K, V, A>,
+        mut f: F,
+    ) -> Tree234<K, V, A> {
+        self.merge(other, |key, lhs, rhs| match (lhs, rhs) {
+            (Some(lhs), Some(rhs)) => Some(f(key, lhs, rhs)),
+            (lhs, rhs) => lhs.or(rhs),
+        })
+    }
+
+    pub fn intersection_with<U, B: Augment<K, U>, F: FnMut(&K, V, U) -> V>(
+        self,
+        other: Tree234<K, U, B>,
+        mut f: F,
+    ) -> Tree234<K, V, A> {
+        self.merge(other, |key, lhs, rhs| match (lhs, rhs) {
+            (Some(lhs), Some(rhs)) => Some(f(key, lhs, rhs)),
+            _ => None,
+        })
+    }
+
+    pub fn difference<U, B: Augment<K, U>>(self, other: Tree234<K, U, B>) -> Tree234<K, V, A> {
+        self.merge(other, |_key, lhs, rhs| match (lhs, rhs) {
+            (Some(lhs), None) => Some(lhs),
+            _ => None,
+        })
+    }
+
+    pub fn symmetric_difference(self, other: Tree234<K, V, A>) -> Tree234<K, V, A> {
+        self.merge(other, |_key, lhs, rhs| match (lhs, rhs) {
+            (Some(lhs), None) => Some(lhs),
+            (None, Some(rhs)) => Some(rhs),
+            _ => None,
+        })
+    }
+
+    // Walk both trees in key order, offering each key with its value from
+    // either side to `pick`, and bulk-build a tree from the values it keeps.
+    // Where both sides hold a key, the one from `self` is kept.
+    fn merge<U, B: Augment<K, U>, F: FnMut(&K, Option<V>, Option<U>) -> Option<V>>(
+        self,
+        other: Tree234<K, U, B>,
+        mut pick: F,
+    ) -> Tree234<K, V, A> {
+        let mut lhs = self.into_iter().peekable();
+        let mut rhs = other.into_iter().peekable();
+        let mut items = Vec::new();
+        loop {
+            let order = match (lhs.peek(), rhs.peek()) {
+                (Some(a), Some(b)) => a.0.cmp(&b.0),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => break,
+            };
+            let (key, a, b) = match order {
+                std::cmp::Ordering::Less => {
+                    let (key, a) = lhs.next().unwrap();
+                    (key, Some(a), None)
+                }
+                std::cmp::Ordering::Equal => {
+                    let (key, a) = lhs.next().unwrap();
+                    let (_, b) = rhs.next().unwrap();
+                    (key, Some(a), Some(b))
+                }
+                std::cmp::Ordering::Greater => {
+                    let (key, b) = rhs.next().unwrap();
+                    (key, None, Some(b))
+                }
+            };
+            if let Some(value) = pick(&key, a, b) {
+                items.push((key, value));
+            }
+        }
+        Tree234::from_strictly_sorted(items)
     }
 
     pub fn clear(&mut self) {
@@ -1474,89 +2018,1258 @@ impl<K: Eq + Ord, V> Tree234<K, V> {
         self.root.visit(visitor);
     }
 
-    pub fn iter(&self) -> Tree234Iterator<'_, K, V> {
+    pub fn iter(&self) -> Tree234Iterator<'_, K, V, A> {
         Tree234Iterator::new(self)
     }
-}
 
-pub struct Tree234Iterator<'a, K: Eq + Ord, V> {
-    items: VecDeque<Either<&'a Item<K, V>, &'a NodeBox<K, V>>>,
-}
+    pub fn keys(&self) -> Keys<'_, K, V, A> {
+        Keys { inner: self.iter() }
+    }
 
-impl<'a, K: Eq + Ord, V> Tree234Iterator<'a, K, V> {
-    pub fn new(tree: &'a Tree234<K, V>) -> Tree234Iterator<'a, K, V> {
-        let mut items = VecDeque::new();
-        items.push_back(Either::Right(&tree.root));
-        Tree234Iterator { items: items }
+    pub fn values(&self) -> Values<'_, K, V, A> {
+        Values { inner: self.iter() }
     }
-}
 
-impl<'a, K: Eq + Ord, V> Iterator for Tree234Iterator<'a, K, V> {
-    type Item = &'a (K, V);
+    pub fn drain(&mut self) -> Drain<'_, K, V, A> {
+        let mut root = Node::empty();
+        std::mem::swap(&mut self.root, &mut root);
+        Drain {
+            inner: IntoIter::new(root),
+            tree: PhantomData,
+        }
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        while let Some(thing) = self.items.pop_front() {
-            match thing {
-                Either::Left(item) => return Some(item),
-                Either::Right(node) => {
-                    match node.as_ref() {
-                        Node::Empty => {
-                            // do nothing!
-                        }
-                        Node::Two(two) => {
-                            let Two {
-                                size: _,
-                                item,
-                                lhs,
-                                rhs,
-                            } = two;
-                            self.items.push_front(Either::Right(rhs));
-                            self.items.push_front(Either::Left(item));
-                            self.items.push_front(Either::Right(lhs));
-                        }
-                        Node::Three(three) => {
-                            let Three {
-                                size: _,
-                                item1,
-                                item2,
-                                lhs,
-                                mid,
-                                rhs,
-                            } = three;
-                            self.items.push_front(Either::Right(rhs));
-                            self.items.push_front(Either::Left(item2));
-                            self.items.push_front(Either::Right(mid));
-                            self.items.push_front(Either::Left(item1));
-                            self.items.push_front(Either::Right(lhs));
-                        }
-                        Node::Four(four) => {
-                            let Four {
-                                size: _,
-                                item1,
-                                item2,
-                                item3,
-                                lhs,
-                                lhs_mid,
-                                rhs_mid,
-                                rhs,
-                            } = four;
-                            self.items.push_front(Either::Right(rhs));
-                            self.items.push_front(Either::Left(item3));
-                            self.items.push_front(Either::Right(rhs_mid));
-                            self.items.push_front(Either::Left(item2));
-                            self.items.push_front(Either::Right(lhs_mid));
-                            self.items.push_front(Either::Left(item1));
-                            self.items.push_front(Either::Right(lhs));
-                        }
-                    }
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, mut f: F) {
+        self.extract_if(|key, value| !f(key, value)).for_each(drop);
+    }
+
+    pub fn extract_if<F: FnMut(&K, &mut V) -> bool>(
+        &mut self,
+        pred: F,
+    ) -> ExtractIf<'_, K, V, F, A> {
+        let mut root = Node::empty();
+        std::mem::swap(&mut self.root, &mut root);
+        ExtractIf {
+            inner: IntoIter::new(root),
+            kept: Vec::new(),
+            pred,
+            tree: self,
+        }
+    }
+
+    pub fn aggregate<Q: Ord + ?Sized, R: RangeBounds<Q>>(&self, range: R) -> A
+    where
+        K: Borrow<Q>,
+    {
+        self.root.aggregate(range.start_bound(), range.end_bound())
+    }
+
+    pub fn range<Q: Ord + ?Sized, R: RangeBounds<Q>>(&self, range: R) -> Range<'_, K, V, A>
+    where
+        K: Borrow<Q>,
+    {
+        Range::new(self, range.start_bound(), range.end_bound())
+    }
+
+    pub fn cursor_front(&self) -> Cursor<'_, K, V, A> {
+        Cursor {
+            tree: self,
+            index: 0,
+        }
+    }
+
+    pub fn cursor_back(&self) -> Cursor<'_, K, V, A> {
+        let index = self.size().saturating_sub(1);
+        Cursor { tree: self, index }
+    }
+
+    pub fn lower_bound<Q: Ord + ?Sized>(&self, bound: Bound<&Q>) -> Cursor<'_, K, V, A>
+    where
+        K: Borrow<Q>,
+    {
+        let index = self.lower_bound_index(bound);
+        Cursor { tree: self, index }
+    }
+
+    pub fn upper_bound<Q: Ord + ?Sized>(&self, bound: Bound<&Q>) -> Cursor<'_, K, V, A>
+    where
+        K: Borrow<Q>,
+    {
+        let index = self.upper_bound_index(bound);
+        Cursor { tree: self, index }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, K, V, A> {
+        CursorMut {
+            tree: self,
+            index: 0,
+        }
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, K, V, A> {
+        let index = self.size().saturating_sub(1);
+        CursorMut { tree: self, index }
+    }
+
+    pub fn lower_bound_mut<Q: Ord + ?Sized>(&mut self, bound: Bound<&Q>) -> CursorMut<'_, K, V, A>
+    where
+        K: Borrow<Q>,
+    {
+        let index = self.lower_bound_index(bound);
+        CursorMut { tree: self, index }
+    }
+
+    pub fn upper_bound_mut<Q: Ord + ?Sized>(&mut self, bound: Bound<&Q>) -> CursorMut<'_, K, V, A>
+    where
+        K: Borrow<Q>,
+    {
+        let index = self.upper_bound_index(bound);
+        CursorMut { tree: self, index }
+    }
+
+    // The index of the first item above the bound, or the size of the tree if none is.
+    fn lower_bound_index<Q: Ord + ?Sized>(&self, bound: Bound<&Q>) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        match bound {
+            Bound::Included(key) => self.rank(key),
+            Bound::Excluded(key) => self.rank(key) + self.contains_key(key) as usize,
+            Bound::Unbounded => 0,
+        }
+    }
+
+    // The index of the last item below the bound, or the size of the tree if none is.
+    fn upper_bound_index<Q: Ord + ?Sized>(&self, bound: Bound<&Q>) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        let count = match bound {
+            Bound::Included(key) => self.rank(key) + self.contains_key(key) as usize,
+            Bound::Excluded(key) => self.rank(key),
+            Bound::Unbounded => self.size(),
+        };
+        match count {
+            0 => self.size(),
+            _ => count - 1,
+        }
+    }
+}
+
+// Only for unaugmented trees: the constructors pin the summary type so that
+// callers need not name it (augmented trees come from `default` or `collect`),
+// and handing out mutable references to values would let summaries go stale.
+impl<K: Eq + Ord, V> Tree234<K, V> {
+    pub fn new() -> Tree234<K, V> {
+        Tree234 {
+            root: Node::empty(),
+        }
+    }
+
+    pub fn from_sorted_iter<I: IntoIterator<Item = Item<K, V>>>(
+        iter: I,
+    ) -> Result<Tree234<K, V>, BuildError> {
+        let items = iter.into_iter().collect::<Vec<Item<K, V>>>();
+        Self::check_sorted(&items)?;
+        Ok(Tree234::from_strictly_sorted(items))
+    }
+
+    // Later items replace earlier ones with the same key.
+    pub fn from_unsorted_vec(items: Vec<Item<K, V>>) -> Tree234<K, V> {
+        Tree234::from_unsorted(items)
+    }
+
+    pub fn get_mut<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        self.root.locate_mut(key).map(|item| &mut item.1)
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        let mut children = [0; MAX_DEPTH];
+        match Node::search_vacant(&mut self.root, &key, &mut children) {
+            Ok(item) => {
+                let item: *mut Item<K, V> = item;
+                Entry::Occupied(OccupiedEntry {
+                    key,
+                    item,
+                    tree: self,
+                })
+            }
+            Err((depth, index)) => Entry::Vacant(VacantEntry {
+                key,
+                children,
+                depth,
+                index,
+                tree: self,
+            }),
+        }
+    }
+
+    pub fn visit_mut<Visitor: FnMut(&K, &mut V)>(&mut self, visitor: &mut Visitor) {
+        self.root.visit_mut(visitor);
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut::new(self)
+    }
+
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            inner: IterMut::new(self),
+        }
+    }
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> Default for Tree234<K, V, A> {
+    fn default() -> Tree234<K, V, A> {
+        Tree234 {
+            root: Node::empty(),
+        }
+    }
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> FromIterator<Item<K, V>> for Tree234<K, V, A> {
+    fn from_iter<I: IntoIterator<Item = Item<K, V>>>(iter: I) -> Tree234<K, V, A> {
+        let items = iter.into_iter().collect::<Vec<Item<K, V>>>();
+        if Tree234::<K, V, A>::check_sorted(&items).is_ok() {
+            Tree234::from_strictly_sorted(items)
+        } else {
+            Tree234::from_unsorted(items)
+        }
+    }
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> Extend<Item<K, V>> for Tree234<K, V, A> {
+    fn extend<I: IntoIterator<Item = Item<K, V>>>(&mut self, iter: I) {
+        let items = iter.into_iter().collect::<Vec<Item<K, V>>>();
+        let sorted = items.windows(2).all(|pair| pair[0].0 < pair[1].0);
+        let above = match (self.last(), items.first()) {
+            (Some(last), Some(first)) => last.0 < first.0,
+            _ => true,
+        };
+        if sorted && above {
+            self.append_ordered(Tree234::from_strictly_sorted(items));
+        } else {
+            for (key, value) in items {
+                self.insert(key, value);
+            }
+        }
+    }
+}
+
+impl<'a, K: Eq + Ord + Copy, V: Copy, A: Augment<K, V>> Extend<(&'a K, &'a V)>
+    for Tree234<K, V, A>
+{
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(key, value)| (*key, *value)));
+    }
+}
+
+impl<K: Eq + Ord + Borrow<Q>, V, Q: Ord + ?Sized, A: Augment<K, V>> std::ops::Index<&Q>
+    for Tree234<K, V, A>
+{
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &V {
+        &self.get(key).expect("key not found").1
+    }
+}
+
+impl<K: Eq + Ord, V, const N: usize> From<[Item<K, V>; N]> for Tree234<K, V> {
+    fn from(items: [Item<K, V>; N]) -> Tree234<K, V> {
+        Tree234::from_iter(items)
+    }
+}
+
+impl<K: Eq + Ord + std::fmt::Debug, V: std::fmt::Debug, A: Augment<K, V>> std::fmt::Debug
+    for Tree234<K, V, A>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.iter().map(|item| (&item.0, &item.1)))
+            .finish()
+    }
+}
+
+impl<K: Eq + Ord, V: PartialEq, A: Augment<K, V>> PartialEq for Tree234<K, V, A> {
+    fn eq(&self, other: &Tree234<K, V, A>) -> bool {
+        self.size() == other.size() && self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Eq + Ord, V: Eq, A: Augment<K, V>> Eq for Tree234<K, V, A> {}
+
+impl<K: Eq + Ord, V: PartialOrd, A: Augment<K, V>> PartialOrd for Tree234<K, V, A> {
+    fn partial_cmp(&self, other: &Tree234<K, V, A>) -> Option<std::cmp::Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<K: Eq + Ord, V: Ord, A: Augment<K, V>> Ord for Tree234<K, V, A> {
+    fn cmp(&self, other: &Tree234<K, V, A>) -> std::cmp::Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<K: Eq + Ord + std::hash::Hash, V: std::hash::Hash, A: Augment<K, V>> std::hash::Hash
+    for Tree234<K, V, A>
+{
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.size().hash(state);
+        for item in self.iter() {
+            item.hash(state);
+        }
+    }
+}
+
+pub enum Entry<'a, K: Eq + Ord, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+impl<'a, K: Eq + Ord, V> Entry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    pub fn or_insert(self, value: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(value),
+        }
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, f: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(f()),
+        }
+    }
+
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, f: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let value = f(&entry.key);
+                entry.insert(value)
+            }
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        match self {
+            Entry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Entry::Occupied(entry)
+            }
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
+
+    pub fn insert(self, value: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(mut entry) => {
+                entry.insert(value);
+                entry.into_mut()
+            }
+            Entry::Vacant(entry) => entry.insert(value),
+        }
+    }
+
+    pub fn remove(self) -> Option<V> {
+        match self {
+            Entry::Occupied(entry) => Some(entry.remove()),
+            Entry::Vacant(_) => None,
+        }
+    }
+}
+
+pub struct OccupiedEntry<'a, K: Eq + Ord, V> {
+    key: K,
+    // Points into a node owned by `tree`. Nothing moves or frees the item
+    // while the entry holds the only borrow of the tree, except `remove`,
+    // which consumes the entry. It cannot be a `&'a mut` held alongside
+    // `tree`, as the removal would then write through one reference to
+    // memory another live argument claims exclusively.
+    item: *mut Item<K, V>,
+    tree: &'a mut Tree234<K, V>,
+}
+
+impl<'a, K: Eq + Ord, V> OccupiedEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        // SAFETY: `item` is valid until the entry is consumed, and shared
+        // here for no longer than `self` is.
+        unsafe { &(*self.item).0 }
+    }
+
+    pub fn get(&self) -> &V {
+        // SAFETY: as for `key`.
+        unsafe { &(*self.item).1 }
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        // SAFETY: `item` is valid until the entry is consumed, and lent out
+        // exclusively for no longer than `self` is borrowed mutably.
+        unsafe { &mut (*self.item).1 }
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        // SAFETY: `item` stays valid for as long as the tree is borrowed, and
+        // consuming the entry gives up the only other way into the tree.
+        unsafe { &mut (*self.item).1 }
+    }
+
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove(self) -> V {
+        self.tree.remove(&self.key).unwrap()
+    }
+
+    pub fn remove_entry(self) -> Item<K, V> {
+        self.tree.remove_entry(&self.key).unwrap()
+    }
+}
+
+pub struct VacantEntry<'a, K: Eq + Ord, V> {
+    key: K,
+    // Where the lookup stopped: the child taken at each of the first `depth`
+    // levels, and the index to insert at in the leaf below them.
+    children: [u8; MAX_DEPTH],
+    depth: usize,
+    index: usize,
+    tree: &'a mut Tree234<K, V>,
+}
+
+impl<'a, K: Eq + Ord, V> VacantEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    pub fn insert(self, value: V) -> &'a mut V {
+        let children = &self.children[..self.depth];
+        &mut Node::insert_vacant(&mut self.tree.root, children, self.index, (self.key, value)).1
+    }
+}
+
+// A cursor sits either on an item, identified by its index in key order, or
+// on a "ghost" position (index == size) that lies between the last item and
+// the first, so moving past either end wraps through it.
+pub struct Cursor<'a, K: Eq + Ord, V, A: Augment<K, V> = ()> {
+    tree: &'a Tree234<K, V, A>,
+    index: usize,
+}
+
+impl<'a, K: Eq + Ord, V, A: Augment<K, V>> Cursor<'a, K, V, A> {
+    pub fn index(&self) -> Option<usize> {
+        match self.index < self.tree.size() {
+            true => Some(self.index),
+            false => None,
+        }
+    }
+
+    pub fn current(&self) -> Option<&'a Item<K, V>> {
+        self.tree.nth(self.index)
+    }
+
+    pub fn key(&self) -> Option<&'a K> {
+        self.current().map(|item| &item.0)
+    }
+
+    pub fn value(&self) -> Option<&'a V> {
+        self.current().map(|item| &item.1)
+    }
+
+    pub fn move_next(&mut self) {
+        self.index = (self.index + 1) % (self.tree.size() + 1);
+    }
+
+    pub fn move_prev(&mut self) {
+        let n = self.tree.size() + 1;
+        self.index = (self.index + n - 1) % n;
+    }
+
+    pub fn peek_next(&self) -> Option<&'a Item<K, V>> {
+        self.tree.nth((self.index + 1) % (self.tree.size() + 1))
+    }
+
+    pub fn peek_prev(&self) -> Option<&'a Item<K, V>> {
+        let n = self.tree.size() + 1;
+        self.tree.nth((self.index + n - 1) % n)
+    }
+}
+
+pub struct CursorMut<'a, K: Eq + Ord, V, A: Augment<K, V> = ()> {
+    tree: &'a mut Tree234<K, V, A>,
+    index: usize,
+}
+
+impl<'a, K: Eq + Ord, V, A: Augment<K, V>> CursorMut<'a, K, V, A> {
+    pub fn index(&self) -> Option<usize> {
+        match self.index < self.tree.size() {
+            true => Some(self.index),
+            false => None,
+        }
+    }
+
+    pub fn key(&self) -> Option<&K> {
+        self.tree.nth(self.index).map(|item| &item.0)
+    }
+
+    pub fn value(&self) -> Option<&V> {
+        self.tree.nth(self.index).map(|item| &item.1)
+    }
+
+    pub fn move_next(&mut self) {
+        self.index = (self.index + 1) % (self.tree.size() + 1);
+    }
+
+    pub fn move_prev(&mut self) {
+        let n = self.tree.size() + 1;
+        self.index = (self.index + n - 1) % n;
+    }
+
+    pub fn peek_next(&self) -> Option<&Item<K, V>> {
+        self.tree.nth((self.index + 1) % (self.tree.size() + 1))
+    }
+
+    pub fn peek_prev(&self) -> Option<&Item<K, V>> {
+        let n = self.tree.size() + 1;
+        self.tree.nth((self.index + n - 1) % n)
+    }
+
+    // Insert an item immediately before the cursor (at the back of the tree
+    // if the cursor is at the ghost position). Panics if the key does not
+    // sort strictly between the cursor's item and its predecessor.
+    pub fn insert_before(&mut self, key: K, value: V) {
+        let ordered = self.peek_prev().is_none_or(|prev| prev.0 < key)
+            && self.key().is_none_or(|next| key < *next);
+        assert!(ordered, "key is out of order for the cursor position");
+        self.tree.insert(key, value);
+        self.index += 1;
+    }
+
+    // Insert an item immediately after the cursor (at the front of the tree
+    // if the cursor is at the ghost position). Panics if the key does not
+    // sort strictly between the cursor's item and its successor.
+    pub fn insert_after(&mut self, key: K, value: V) {
+        let ordered = self.key().is_none_or(|prev| *prev < key)
+            && self.peek_next().is_none_or(|next| key < next.0);
+        assert!(ordered, "key is out of order for the cursor position");
+        let ghost = self.index == self.tree.size();
+        self.tree.insert(key, value);
+        if ghost {
+            self.index += 1;
+        }
+    }
+
+    // Remove the cursor's item, leaving the cursor on the item that followed it.
+    pub fn remove_current(&mut self) -> Option<Item<K, V>> {
+        self.tree.remove_nth(self.index)
+    }
+}
+
+impl<K: Eq + Ord, V> CursorMut<'_, K, V> {
+    pub fn current(&mut self) -> Option<(&K, &mut V)> {
+        self.tree
+            .root
+            .nth_mut(self.index)
+            .map(|item| (&item.0, &mut item.1))
+    }
+
+    pub fn value_mut(&mut self) -> Option<&mut V> {
+        self.current().map(|(_, value)| value)
+    }
+
+    // Returns the previous value, or `None` (dropping `value`) at the ghost position.
+    pub fn replace_value(&mut self, value: V) -> Option<V> {
+        self.value_mut()
+            .map(|current| std::mem::replace(current, value))
+    }
+}
+
+// One end of an in-order walk, kept as the path of nodes from the root down
+// to the next item, each with the index of the next item to take from it:
+// counting up from the left for a front edge, and down from the right (as
+// the number of items still to take) for a back edge. Empty subtrees are
+// never pushed.
+struct Edge<'a, K: Eq + Ord, V, A: Augment<K, V> = ()> {
+    nodes: [Option<&'a Node<K, V, A>>; MAX_DEPTH],
+    positions: [u8; MAX_DEPTH],
+    depth: usize,
+}
+
+impl<'a, K: Eq + Ord, V, A: Augment<K, V>> Edge<'a, K, V, A> {
+    fn new() -> Edge<'a, K, V, A> {
+        Edge {
+            nodes: [None; MAX_DEPTH],
+            positions: [0; MAX_DEPTH],
+            depth: 0,
+        }
+    }
+
+    fn push(&mut self, node: &'a Node<K, V, A>, position: usize) {
+        self.nodes[self.depth] = Some(node);
+        self.positions[self.depth] = position as u8;
+        self.depth += 1;
+    }
+
+    fn push_first(&mut self, mut node: &'a Node<K, V, A>) {
+        while !node.is_empty() {
+            self.push(node, 0);
+            node = node.child(0);
+        }
+    }
+
+    fn push_last(&mut self, mut node: &'a Node<K, V, A>) {
+        while !node.is_empty() {
+            let n = node.len();
+            self.push(node, n);
+            node = node.child(n);
+        }
+    }
+
+    // Descend to the first item at or above `lower`, returning the number of
+    // items below it.
+    fn seek_lower<Q: Ord + ?Sized>(
+        &mut self,
+        mut node: &'a Node<K, V, A>,
+        lower: Bound<&Q>,
+    ) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        let mut before = 0;
+        while !node.is_empty() {
+            let n = node.len();
+            let i = match lower {
+                Bound::Included(key) => (0..n)
+                    .take_while(|i| node.item(*i).0.borrow() < key)
+                    .count(),
+                Bound::Excluded(key) => (0..n)
+                    .take_while(|i| node.item(*i).0.borrow() <= key)
+                    .count(),
+                Bound::Unbounded => 0,
+            };
+            before += i + (0..i).map(|c| node.child(c).size()).sum::<usize>();
+            self.push(node, i);
+            node = node.child(i);
+        }
+        before
+    }
+
+    // Descend to the last item at or below `upper`, returning the number of
+    // items up to and including it.
+    fn seek_upper<Q: Ord + ?Sized>(
+        &mut self,
+        mut node: &'a Node<K, V, A>,
+        upper: Bound<&Q>,
+    ) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        let mut upto = 0;
+        while !node.is_empty() {
+            let n = node.len();
+            let j = match upper {
+                Bound::Included(key) => (0..n)
+                    .take_while(|j| node.item(*j).0.borrow() <= key)
+                    .count(),
+                Bound::Excluded(key) => (0..n)
+                    .take_while(|j| node.item(*j).0.borrow() < key)
+                    .count(),
+                Bound::Unbounded => n,
+            };
+            upto += j + (0..j).map(|c| node.child(c).size()).sum::<usize>();
+            self.push(node, j);
+            node = node.child(j);
+        }
+        upto
+    }
+
+    fn next_front(&mut self) -> Option<&'a Item<K, V>> {
+        while self.depth > 0 {
+            let top = self.depth - 1;
+            let node = self.nodes[top].unwrap();
+            let i = self.positions[top] as usize;
+            if i < node.len() {
+                self.positions[top] += 1;
+                self.push_first(node.child(i + 1));
+                return Some(node.item(i));
+            }
+            self.depth -= 1;
+        }
+        None
+    }
+
+    fn next_back(&mut self) -> Option<&'a Item<K, V>> {
+        while self.depth > 0 {
+            let top = self.depth - 1;
+            let node = self.nodes[top].unwrap();
+            let j = self.positions[top] as usize;
+            if j > 0 {
+                self.positions[top] -= 1;
+                self.push_last(node.child(j - 1));
+                return Some(node.item(j - 1));
+            }
+            self.depth -= 1;
+        }
+        None
+    }
+}
+
+pub struct Tree234Iterator<'a, K: Eq + Ord, V, A: Augment<K, V> = ()> {
+    front: Edge<'a, K, V, A>,
+    back: Edge<'a, K, V, A>,
+    remaining: usize,
+}
+
+impl<'a, K: Eq + Ord, V, A: Augment<K, V>> Tree234Iterator<'a, K, V, A> {
+    pub fn new(tree: &'a Tree234<K, V, A>) -> Tree234Iterator<'a, K, V, A> {
+        let mut front = Edge::new();
+        let mut back = Edge::new();
+        front.seek_lower(&tree.root, Bound::Unbounded);
+        back.seek_upper(&tree.root, Bound::Unbounded);
+        Tree234Iterator {
+            front,
+            back,
+            remaining: tree.size(),
+        }
+    }
+}
+
+impl<'a, K: Eq + Ord, V, A: Augment<K, V>> Iterator for Tree234Iterator<'a, K, V, A> {
+    type Item = &'a (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.front.next_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> DoubleEndedIterator for Tree234Iterator<'_, K, V, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.back.next_back()
+    }
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> ExactSizeIterator for Tree234Iterator<'_, K, V, A> {}
+
+pub struct Range<'a, K: Eq + Ord, V, A: Augment<K, V> = ()> {
+    front: Edge<'a, K, V, A>,
+    back: Edge<'a, K, V, A>,
+    remaining: usize,
+}
+
+impl<'a, K: Eq + Ord, V, A: Augment<K, V>> Range<'a, K, V, A> {
+    fn new<Q: Ord + ?Sized>(
+        tree: &'a Tree234<K, V, A>,
+        lower: Bound<&Q>,
+        upper: Bound<&Q>,
+    ) -> Range<'a, K, V, A>
+    where
+        K: Borrow<Q>,
+    {
+        let mut front = Edge::new();
+        let mut back = Edge::new();
+        let before = front.seek_lower(&tree.root, lower);
+        let upto = back.seek_upper(&tree.root, upper);
+        Range {
+            front,
+            back,
+            remaining: upto.saturating_sub(before),
+        }
+    }
+}
+
+impl<'a, K: Eq + Ord, V, A: Augment<K, V>> Iterator for Range<'a, K, V, A> {
+    type Item = &'a (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.front.next_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> DoubleEndedIterator for Range<'_, K, V, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.back.next_back()
+    }
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> ExactSizeIterator for Range<'_, K, V, A> {}
+
+type PendingMut<'a, K, V> = Either<(&'a K, &'a mut V), &'a mut NodeBox<K, V>>;
+
+pub struct IterMut<'a, K: Eq + Ord, V> {
+    items: VecDeque<PendingMut<'a, K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K: Eq + Ord, V> IterMut<'a, K, V> {
+    fn new(tree: &'a mut Tree234<K, V>) -> IterMut<'a, K, V> {
+        let remaining = tree.size();
+        let mut items = VecDeque::new();
+        items.push_back(Either::Right(&mut tree.root));
+        IterMut { items, remaining }
+    }
+}
+
+impl<'a, K: Eq + Ord, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = next_pending_mut(&mut self.items, true)?;
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K: Eq + Ord, V> DoubleEndedIterator for IterMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = next_pending_mut(&mut self.items, false)?;
+        self.remaining -= 1;
+        Some(item)
+    }
+}
+
+impl<K: Eq + Ord, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+pub struct ValuesMut<'a, K: Eq + Ord, V> {
+    inner: IterMut<'a, K, V>,
+}
+
+impl<'a, K: Eq + Ord, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K: Eq + Ord, V> DoubleEndedIterator for ValuesMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, value)| value)
+    }
+}
+
+impl<K: Eq + Ord, V> ExactSizeIterator for ValuesMut<'_, K, V> {}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> IntoIterator for Tree234<K, V, A> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, A>;
+
+    fn into_iter(self) -> IntoIter<K, V, A> {
+        IntoIter::new(self.root)
+    }
+}
+
+impl<'a, K: Eq + Ord, V, A: Augment<K, V>> IntoIterator for &'a Tree234<K, V, A> {
+    type Item = &'a (K, V);
+    type IntoIter = Tree234Iterator<'a, K, V, A>;
+
+    fn into_iter(self) -> Tree234Iterator<'a, K, V, A> {
+        self.iter()
+    }
+}
+
+impl<'a, K: Eq + Ord, V> IntoIterator for &'a mut Tree234<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        self.iter_mut()
+    }
+}
+
+pub struct Keys<'a, K: Eq + Ord, V, A: Augment<K, V> = ()> {
+    inner: Tree234Iterator<'a, K, V, A>,
+}
+
+impl<'a, K: Eq + Ord, V, A: Augment<K, V>> Iterator for Keys<'a, K, V, A> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|item| &item.0)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> DoubleEndedIterator for Keys<'_, K, V, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|item| &item.0)
+    }
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> ExactSizeIterator for Keys<'_, K, V, A> {}
+
+pub struct Values<'a, K: Eq + Ord, V, A: Augment<K, V> = ()> {
+    inner: Tree234Iterator<'a, K, V, A>,
+}
+
+impl<'a, K: Eq + Ord, V, A: Augment<K, V>> Iterator for Values<'a, K, V, A> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|item| &item.1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> DoubleEndedIterator for Values<'_, K, V, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|item| &item.1)
+    }
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> ExactSizeIterator for Values<'_, K, V, A> {}
+
+type PendingOwned<K, V, A> = Either<Item<K, V>, NodeBox<K, V, A>>;
+
+pub struct IntoIter<K: Eq + Ord, V, A: Augment<K, V> = ()> {
+    items: VecDeque<PendingOwned<K, V, A>>,
+    remaining: usize,
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> IntoIter<K, V, A> {
+    fn new(root: NodeBox<K, V, A>) -> IntoIter<K, V, A> {
+        let remaining = root.size();
+        let mut items = VecDeque::new();
+        items.push_back(Either::Right(root));
+        IntoIter { items, remaining }
+    }
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> Iterator for IntoIter<K, V, A> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = next_pending_owned(&mut self.items, true)?;
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> DoubleEndedIterator for IntoIter<K, V, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = next_pending_owned(&mut self.items, false)?;
+        self.remaining -= 1;
+        Some(item)
+    }
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> ExactSizeIterator for IntoIter<K, V, A> {}
+
+pub struct Drain<'a, K: Eq + Ord, V, A: Augment<K, V> = ()> {
+    inner: IntoIter<K, V, A>,
+    tree: PhantomData<&'a mut Tree234<K, V, A>>,
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> Iterator for Drain<'_, K, V, A> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> DoubleEndedIterator for Drain<'_, K, V, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<K: Eq + Ord, V, A: Augment<K, V>> ExactSizeIterator for Drain<'_, K, V, A> {}
+
+// The tree is emptied while extraction is under way, and rebuilt from the
+// items that were kept (and any not yet visited) when the iterator is dropped.
+pub struct ExtractIf<'a, K: Eq + Ord, V, F: FnMut(&K, &mut V) -> bool, A: Augment<K, V> = ()> {
+    inner: IntoIter<K, V, A>,
+    kept: Vec<Item<K, V>>,
+    pred: F,
+    tree: &'a mut Tree234<K, V, A>,
+}
+
+impl<K: Eq + Ord, V, F: FnMut(&K, &mut V) -> bool, A: Augment<K, V>> Iterator
+    for ExtractIf<'_, K, V, F, A>
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (key, mut value) in self.inner.by_ref() {
+            if (self.pred)(&key, &mut value) {
+                return Some((key, value));
+            }
+            self.kept.push((key, value));
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inner.size_hint().1)
+    }
+}
+
+impl<K: Eq + Ord, V, F: FnMut(&K, &mut V) -> bool, A: Augment<K, V>> Drop
+    for ExtractIf<'_, K, V, F, A>
+{
+    fn drop(&mut self) {
+        let mut kept = std::mem::take(&mut self.kept);
+        kept.extend(self.inner.by_ref());
+        *self.tree = Tree234::from_strictly_sorted(kept);
+    }
+}
+
+// The mutable and owning iterators keep a queue of items and unexpanded
+// subtrees in key order, so both ends can be consumed independently:
+// expanding a subtree at the front or the back replaces it in place with its
+// parts, and the two ends meet when the queue runs dry.
+fn push_parts<T, const N: usize>(items: &mut VecDeque<T>, parts: [T; N], front: bool) {
+    if front {
+        for part in parts.into_iter().rev() {
+            items.push_front(part);
+        }
+    } else {
+        for part in parts {
+            items.push_back(part);
+        }
+    }
+}
+
+fn next_pending_mut<'a, K: Eq + Ord, V>(
+    items: &mut VecDeque<PendingMut<'a, K, V>>,
+    front: bool,
+) -> Option<(&'a K, &'a mut V)> {
+    loop {
+        let thing = if front {
+            items.pop_front()
+        } else {
+            items.pop_back()
+        }?;
+        match thing {
+            Either::Left(item) => return Some(item),
+            Either::Right(node) => match node.as_mut() {
+                None | Some(Node::Empty) => {}
+                Some(Node::Two(two)) => {
+                    let Two {
+                        size: _,
+                        summary: _,
+                        item,
+                        lhs,
+                        rhs,
+                    } = two;
+                    push_parts(
+                        items,
+                        [
+                            Either::Right(lhs),
+                            Either::Left((&item.0, &mut item.1)),
+                            Either::Right(rhs),
+                        ],
+                        front,
+                    );
+                }
+                Some(Node::Three(three)) => {
+                    let Three {
+                        size: _,
+                        summary: _,
+                        item1,
+                        item2,
+                        lhs,
+                        mid,
+                        rhs,
+                    } = three;
+                    push_parts(
+                        items,
+                        [
+                            Either::Right(lhs),
+                            Either::Left((&item1.0, &mut item1.1)),
+                            Either::Right(mid),
+                            Either::Left((&item2.0, &mut item2.1)),
+                            Either::Right(rhs),
+                        ],
+                        front,
+                    );
+                }
+                Some(Node::Four(four)) => {
+                    let Four {
+                        size: _,
+                        summary: _,
+                        item1,
+                        item2,
+                        item3,
+                        lhs,
+                        lhs_mid,
+                        rhs_mid,
+                        rhs,
+                    } = four;
+                    push_parts(
+                        items,
+                        [
+                            Either::Right(lhs),
+                            Either::Left((&item1.0, &mut item1.1)),
+                            Either::Right(lhs_mid),
+                            Either::Left((&item2.0, &mut item2.1)),
+                            Either::Right(rhs_mid),
+                            Either::Left((&item3.0, &mut item3.1)),
+                            Either::Right(rhs),
+                        ],
+                        front,
+                    );
+                }
+            },
+        }
+    }
+}
+
+fn next_pending_owned<K: Eq + Ord, V, A: Augment<K, V>>(
+    items: &mut VecDeque<PendingOwned<K, V, A>>,
+    front: bool,
+) -> Option<Item<K, V>> {
+    loop {
+        let thing = if front {
+            items.pop_front()
+        } else {
+            items.pop_back()
+        }?;
+        match thing {
+            Either::Left(item) => return Some(item),
+            Either::Right(node) => match node.into_node() {
+                Node::Empty => {}
+                Node::Two(two) => {
+                    let Two {
+                        size: _,
+                        summary: _,
+                        item,
+                        lhs,
+                        rhs,
+                    } = two;
+                    push_parts(
+                        items,
+                        [Either::Right(lhs), Either::Left(item), Either::Right(rhs)],
+                        front,
+                    );
                 }
-            };
+                Node::Three(three) => {
+                    let Three {
+                        size: _,
+                        summary: _,
+                        item1,
+                        item2,
+                        lhs,
+                        mid,
+                        rhs,
+                    } = three;
+                    push_parts(
+                        items,
+                        [
+                            Either::Right(lhs),
+                            Either::Left(item1),
+                            Either::Right(mid),
+                            Either::Left(item2),
+                            Either::Right(rhs),
+                        ],
+                        front,
+                    );
+                }
+                Node::Four(four) => {
+                    let Four {
+                        size: _,
+                        summary: _,
+                        item1,
+                        item2,
+                        item3,
+                        lhs,
+                        lhs_mid,
+                        rhs_mid,
+                        rhs,
+                    } = four;
+                    push_parts(
+                        items,
+                        [
+                            Either::Right(lhs),
+                            Either::Left(item1),
+                            Either::Right(lhs_mid),
+                            Either::Left(item2),
+                            Either::Right(rhs_mid),
+                            Either::Left(item3),
+                            Either::Right(rhs),
+                        ],
+                        front,
+                    );
+                }
+            },
         }
-        None
     }
 }
 
 #[cfg(test)]
+#[allow(
+    clippy::needless_range_loop,
+    clippy::manual_swap,
+    clippy::map_clone,
+    clippy::clone_on_copy
+)]
 mod tests {
     use rand::rngs::StdRng;
     use rand::seq::SliceRandom;
@@ -1564,6 +3277,37 @@ mod tests {
 
     use super::*;
 
+    // Check the structural invariants of a subtree, returning its height.
+    fn check_node<K: Eq + Ord, V, A: Augment<K, V>>(node: &NodeBox<K, V, A>) -> usize {
+        if node.is_empty() {
+            assert!(node.0.is_none());
+            return 0;
+        }
+        let n = node.len();
+        let height = check_node(node.child(0));
+        let mut size = n;
+        for i in 0..=n {
+            let child = node.child(i);
+            assert_eq!(check_node(child), height);
+            size += child.size();
+            if i > 0 {
+                assert!(child.first().is_none_or(|item| node.item(i - 1).0 < item.0));
+            }
+            if i < n {
+                assert!(child.last().is_none_or(|item| item.0 < node.item(i).0));
+            }
+        }
+        for i in 1..n {
+            assert!(node.item(i - 1).0 < node.item(i).0);
+        }
+        assert_eq!(node.size(), size);
+        height + 1
+    }
+
+    fn check_tree<K: Eq + Ord, V, A: Augment<K, V>>(tree: &Tree234<K, V, A>) {
+        check_node(&tree.root);
+    }
+
     #[test]
     fn empty_1() {
         let tree: Tree234<i32, usize> = Tree234::new();
@@ -1671,6 +3415,959 @@ mod tests {
         }
     }
 
+    #[test]
+    fn order_statistics_1() {
+        let mut rng = StdRng::seed_from_u64(19u64);
+        let mut tree: Tree234<u64, usize> = Tree234::new();
+        let mut xs: Vec<u64> = Vec::new();
+        for i in 0..1000 {
+            let x: u64 = (rng.gen::<u64>() & 0xffff) * 2;
+            tree.insert(x, i);
+            xs.push(x);
+        }
+        xs.sort();
+        xs.dedup();
+        assert_eq!(tree.size(), xs.len());
+        for i in 0..xs.len() {
+            let x = xs[i];
+            assert_eq!(tree.nth(i).map(|item| item.0), Some(x));
+            assert_eq!(tree.rank(&x), i);
+            assert_eq!(tree.rank(&(x + 1)), i + 1);
+        }
+        assert_eq!(tree.nth(xs.len()), None);
+        assert_eq!(tree.rank(&0), 0);
+        assert_eq!(tree.rank(&u64::MAX), xs.len());
+    }
+
+    #[test]
+    fn range_1() {
+        let xs: Vec<i32> = (0..200).map(|i| i * 3).collect();
+        let mut tree: Tree234<i32, usize> = Tree234::new();
+        for i in 0..xs.len() {
+            tree.insert(xs[i], i);
+        }
+        let keys = |r: Range<'_, i32, usize>| r.map(|item| item.0).collect::<Vec<i32>>();
+        let want =
+            |f: &dyn Fn(i32) -> bool| xs.iter().cloned().filter(|x| f(*x)).collect::<Vec<i32>>();
+        for a in -5..605 {
+            for b in (a - 5)..(a + 40) {
+                assert_eq!(keys(tree.range(a..b)), want(&|x| a <= x && x < b));
+                assert_eq!(keys(tree.range(a..=b)), want(&|x| a <= x && x <= b));
+                let r = (Bound::Excluded(a), Bound::Excluded(b));
+                assert_eq!(keys(tree.range(r)), want(&|x| a < x && x < b));
+                let r = (Bound::Excluded(a), Bound::Included(b));
+                assert_eq!(keys(tree.range(r)), want(&|x| a < x && x <= b));
+            }
+            assert_eq!(keys(tree.range(a..)), want(&|x| a <= x));
+            assert_eq!(keys(tree.range(..a)), want(&|x| x < a));
+            assert_eq!(keys(tree.range(..=a)), want(&|x| x <= a));
+        }
+        assert_eq!(keys(tree.range(..)), xs);
+    }
+
+    #[test]
+    fn neighbours_1() {
+        let mut rng = StdRng::seed_from_u64(23u64);
+        let mut tree: Tree234<u64, usize> = Tree234::new();
+        assert_eq!(tree.first(), None);
+        assert_eq!(tree.last(), None);
+        assert_eq!(tree.floor(&7), None);
+        assert_eq!(tree.successor(&7), None);
+        let mut xs: Vec<u64> = Vec::new();
+        for i in 0..500 {
+            let x: u64 = rng.gen::<u64>() & 0xfff;
+            tree.insert(x, i);
+            xs.push(x);
+        }
+        xs.sort();
+        xs.dedup();
+        let key = |item: Option<&(u64, usize)>| item.map(|item| item.0);
+        assert_eq!(key(tree.first()), xs.first().copied());
+        assert_eq!(key(tree.last()), xs.last().copied());
+        for x in 0..0x1001 {
+            let floor = xs.iter().rev().find(|y| **y <= x).copied();
+            let ceiling = xs.iter().find(|y| **y >= x).copied();
+            let predecessor = xs.iter().rev().find(|y| **y < x).copied();
+            let successor = xs.iter().find(|y| **y > x).copied();
+            assert_eq!(key(tree.floor(&x)), floor);
+            assert_eq!(key(tree.ceiling(&x)), ceiling);
+            assert_eq!(key(tree.predecessor(&x)), predecessor);
+            assert_eq!(key(tree.successor(&x)), successor);
+        }
+    }
+
+    #[test]
+    fn pop_1() {
+        let mut rng = StdRng::seed_from_u64(29u64);
+        let mut tree: Tree234<u64, u64> = Tree234::new();
+        assert_eq!(tree.pop_first(), None);
+        assert_eq!(tree.pop_last(), None);
+        let mut xs: Vec<u64> = Vec::new();
+        for i in 0..1000 {
+            let x: u64 = rng.gen::<u64>() & 0xffff;
+            tree.insert(x, i);
+            xs.push(x);
+        }
+        xs.sort();
+        xs.dedup();
+        let mut lo = 0;
+        let mut hi = xs.len();
+        while lo < hi {
+            assert_eq!(tree.size(), hi - lo);
+            if rng.gen::<bool>() {
+                assert_eq!(tree.pop_first().map(|item| item.0), Some(xs[lo]));
+                lo += 1;
+            } else {
+                hi -= 1;
+                assert_eq!(tree.pop_last().map(|item| item.0), Some(xs[hi]));
+            }
+            let mut ys: Vec<u64> = Vec::new();
+            tree.visit(&mut |item| ys.push(item.0));
+            assert_eq!(ys, xs[lo..hi]);
+        }
+        assert_eq!(tree.pop_first(), None);
+        assert_eq!(tree.pop_last(), None);
+    }
+
+    #[test]
+    fn entry_1() {
+        let mut rng = StdRng::seed_from_u64(31u64);
+        let mut tree: Tree234<u64, usize> = Tree234::new();
+        let mut counts: Vec<usize> = vec![0; 64];
+        for _i in 0..5000 {
+            let x: u64 = rng.gen::<u64>() & 0x3f;
+            *tree.entry(x).or_insert(0) += 1;
+            counts[x as usize] += 1;
+        }
+        for x in 0..64 {
+            let count = tree.get(&x).map(|item| item.1).unwrap_or(0);
+            assert_eq!(count, counts[x as usize]);
+        }
+
+        let mut tree: Tree234<i32, i32> = Tree234::new();
+        for i in 0..100 {
+            tree.entry(i * 2)
+                .and_modify(|v| *v += 1)
+                .or_insert_with(|| i);
+        }
+        for i in 0..100 {
+            tree.entry(i).and_modify(|v| *v += 1000).or_default();
+        }
+        assert_eq!(tree.size(), 150);
+        for i in 0..100 {
+            let expect = if i % 2 == 0 { i / 2 + 1000 } else { 0 };
+            assert_eq!(tree.get(&i), Some(&(i, expect)));
+        }
+        match tree.entry(10) {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(entry.key(), &10);
+                assert_eq!(entry.insert(-10), 1005);
+                assert_eq!(entry.get(), &-10);
+                assert_eq!(entry.remove(), -10);
+            }
+            Entry::Vacant(_) => panic!("expected an occupied entry"),
+        }
+        assert_eq!(tree.get(&10), None);
+        match tree.entry(10) {
+            Entry::Occupied(_) => panic!("expected a vacant entry"),
+            Entry::Vacant(entry) => {
+                assert_eq!(entry.key(), &10);
+                *entry.insert(3) *= 7;
+            }
+        }
+        assert_eq!(tree.get(&10), Some(&(10, 21)));
+        assert_eq!(tree.entry(11).remove(), Some(0));
+        assert_eq!(tree.entry(11).remove(), None);
+        assert_eq!(tree.size(), 149);
+    }
+
+    #[test]
+    fn entry_2() {
+        let mut rng = StdRng::seed_from_u64(37u64);
+        let mut tree: Tree234<u32, u32> = Tree234::new();
+        let mut model = std::collections::BTreeMap::new();
+        for i in 0..5000u32 {
+            let x = rng.gen::<u32>() & 0x3ff;
+            if rng.gen::<u32>() % 4 == 0 {
+                assert_eq!(tree.entry(x).remove(), model.remove(&x));
+            } else {
+                *tree.entry(x).or_insert(i) += 1;
+                *model.entry(x).or_insert(i) += 1;
+            }
+            assert_eq!(tree.size(), model.len());
+            if i % 61 == 0 {
+                check_tree(&tree);
+            }
+        }
+        check_tree(&tree);
+        assert!(tree.iter().map(|item| (&item.0, &item.1)).eq(model.iter()));
+    }
+
+    #[test]
+    fn mutation_1() {
+        let mut tree: Tree234<i32, i32> = Tree234::new();
+        for i in 0..300 {
+            tree.insert((i * 7) % 300, i);
+        }
+        assert_eq!(tree.get_mut(&300), None);
+        *tree.get_mut(&7).unwrap() = -1;
+        assert_eq!(tree.get(&7), Some(&(7, -1)));
+
+        for (k, v) in tree.iter_mut() {
+            *v = *k * 2;
+        }
+        for i in 0..300 {
+            assert_eq!(tree.get(&i), Some(&(i, i * 2)));
+        }
+
+        for v in tree.values_mut() {
+            *v += 1;
+        }
+        let mut n = 0;
+        tree.visit_mut(&mut |k, v| {
+            assert_eq!(*k, n);
+            assert_eq!(*v, n * 2 + 1);
+            *v = -*k;
+            n += 1;
+        });
+        assert_eq!(n, 300);
+        let ys = tree.iter().map(|item| item.1).collect::<Vec<i32>>();
+        assert_eq!(ys, (0..300).map(|i| -i).collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn owning_iterators_1() {
+        let xs: Vec<i32> = vec![
+            0, 22, 11, 2, 16, 4, 18, 7, 23, 15, 14, 5, 10, 17, 19, 20, 6, 24, 8, 12, 13, 9, 3, 21,
+            1,
+        ];
+        let mut tree: Tree234<i32, String> = Tree234::new();
+        for i in 0..xs.len() {
+            tree.insert(xs[i], format!("{}", xs[i]));
+        }
+        let keys = tree.keys().cloned().collect::<Vec<i32>>();
+        assert_eq!(keys, (0..25).collect::<Vec<i32>>());
+        let values = tree.values().cloned().collect::<Vec<String>>();
+        assert_eq!(
+            values,
+            (0..25).map(|i| format!("{}", i)).collect::<Vec<String>>()
+        );
+        let mut n = 0;
+        for item in &tree {
+            assert_eq!(item.0, n);
+            n += 1;
+        }
+        assert_eq!(n, 25);
+        for (_k, v) in &mut tree {
+            v.push('!');
+        }
+
+        let drained = tree.drain().take(5).collect::<Vec<(i32, String)>>();
+        assert_eq!(drained.len(), 5);
+        assert_eq!(drained[4], (4, String::from("4!")));
+        assert_eq!(tree.size(), 0);
+
+        for i in 0..xs.len() {
+            tree.insert(xs[i], format!("{}", xs[i]));
+        }
+        let items = tree.into_iter().collect::<Vec<(i32, String)>>();
+        assert_eq!(items.len(), 25);
+        for i in 0..items.len() {
+            assert_eq!(items[i], (i as i32, format!("{}", i)));
+        }
+    }
+
+    #[test]
+    fn double_ended_1() {
+        let mut rng = StdRng::seed_from_u64(37u64);
+        let mut tree: Tree234<u64, u64> = Tree234::new();
+        for i in 0..1000 {
+            tree.insert(rng.gen::<u64>() & 0xffff, i);
+        }
+        let xs = tree.keys().cloned().collect::<Vec<u64>>();
+        let n = xs.len();
+        assert_eq!(tree.iter().len(), n);
+        assert_eq!(
+            tree.keys().rev().cloned().collect::<Vec<u64>>(),
+            xs.iter().rev().cloned().collect::<Vec<u64>>()
+        );
+
+        // Consume from both ends in a random interleaving until they meet.
+        for _i in 0..20 {
+            let mut iter = tree.iter();
+            let mut lo = 0;
+            let mut hi = n;
+            while lo < hi {
+                assert_eq!(iter.len(), hi - lo);
+                if rng.gen::<bool>() {
+                    assert_eq!(iter.next().map(|item| item.0), Some(xs[lo]));
+                    lo += 1;
+                } else {
+                    hi -= 1;
+                    assert_eq!(iter.next_back().map(|item| item.0), Some(xs[hi]));
+                }
+            }
+            assert_eq!(iter.len(), 0);
+            assert_eq!(iter.next(), None);
+            assert_eq!(iter.next_back(), None);
+        }
+
+        for _i in 0..100 {
+            let a = rng.gen::<u64>() & 0xffff;
+            let b = a + (rng.gen::<u64>() & 0xfff);
+            let ys = xs
+                .iter()
+                .cloned()
+                .filter(|x| a <= *x && *x < b)
+                .collect::<Vec<u64>>();
+            let range = tree.range(a..b);
+            assert_eq!(range.len(), ys.len());
+            let zs = range.rev().map(|item| item.0).collect::<Vec<u64>>();
+            assert_eq!(zs, ys.iter().rev().cloned().collect::<Vec<u64>>());
+        }
+
+        assert_eq!(tree.values().len(), n);
+        assert_eq!(tree.iter_mut().len(), n);
+        assert_eq!(tree.values_mut().rev().len(), n);
+        if let Some(v) = tree.values_mut().next_back() {
+            *v = 0;
+        }
+        assert_eq!(tree.last().map(|item| item.1), Some(0));
+
+        let mut into_iter = tree.into_iter();
+        assert_eq!(into_iter.len(), n);
+        assert_eq!(into_iter.next_back().map(|item| item.0), Some(xs[n - 1]));
+        assert_eq!(into_iter.next().map(|item| item.0), Some(xs[0]));
+        assert_eq!(into_iter.len(), n - 2);
+        let rest = into_iter.rev().map(|item| item.0).collect::<Vec<u64>>();
+        assert_eq!(
+            rest,
+            xs[1..n - 1].iter().rev().cloned().collect::<Vec<u64>>()
+        );
+    }
+
+    #[test]
+    fn cursor_1() {
+        let mut tree: Tree234<i32, i32> = Tree234::new();
+        for i in 0..100 {
+            tree.insert(i * 2, i);
+        }
+        let mut cursor = tree.cursor_front();
+        for i in 0..100 {
+            assert_eq!(cursor.index(), Some(i as usize));
+            assert_eq!(cursor.key(), Some(&(i * 2)));
+            assert_eq!(cursor.value(), Some(&i));
+            cursor.move_next();
+        }
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&(0, 0)));
+        assert_eq!(cursor.peek_prev(), Some(&(198, 99)));
+        cursor.move_next();
+        assert_eq!(cursor.key(), Some(&0));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.key(), Some(&198));
+        assert_eq!(tree.cursor_back().key(), Some(&198));
+
+        assert_eq!(tree.lower_bound(Bound::Included(&10)).key(), Some(&10));
+        assert_eq!(tree.lower_bound(Bound::Excluded(&10)).key(), Some(&12));
+        assert_eq!(tree.lower_bound(Bound::Included(&11)).key(), Some(&12));
+        assert_eq!(tree.lower_bound(Bound::Excluded(&198)).key(), None);
+        assert_eq!(tree.lower_bound(Bound::Unbounded).key(), Some(&0));
+        assert_eq!(tree.upper_bound(Bound::Included(&10)).key(), Some(&10));
+        assert_eq!(tree.upper_bound(Bound::Excluded(&10)).key(), Some(&8));
+        assert_eq!(tree.upper_bound(Bound::Included(&11)).key(), Some(&10));
+        assert_eq!(tree.upper_bound(Bound::Excluded(&0)).key(), None);
+        assert_eq!(tree.upper_bound(Bound::Unbounded).key(), Some(&198));
+        let cursor = tree.lower_bound(Bound::Included(&11));
+        assert_eq!(cursor.peek_prev(), Some(&(10, 5)));
+        assert_eq!(cursor.peek_next(), Some(&(14, 7)));
+
+        let empty: Tree234<i32, i32> = Tree234::new();
+        assert_eq!(empty.cursor_front().current(), None);
+        assert_eq!(empty.cursor_back().peek_next(), None);
+    }
+
+    #[test]
+    fn cursor_mut_1() {
+        let mut tree: Tree234<i32, i32> = Tree234::new();
+        for i in 0..100 {
+            tree.insert(i * 2, i);
+        }
+
+        // Remove every multiple of 4 in a single sweep.
+        let mut cursor = tree.cursor_front_mut();
+        while let Some(key) = cursor.key().cloned() {
+            if key % 4 == 0 {
+                assert_eq!(cursor.remove_current(), Some((key, key / 2)));
+            } else {
+                *cursor.value_mut().unwrap() *= 10;
+                cursor.move_next();
+            }
+        }
+        let xs = tree.iter().cloned().collect::<Vec<(i32, i32)>>();
+        let ys = (0..50)
+            .map(|i| (i * 4 + 2, (i * 2 + 1) * 10))
+            .collect::<Vec<(i32, i32)>>();
+        assert_eq!(xs, ys);
+
+        let mut cursor = tree.lower_bound_mut(Bound::Included(&10));
+        assert_eq!(cursor.key(), Some(&10));
+        assert_eq!(cursor.replace_value(-1), Some(50));
+        cursor.insert_before(8, -2);
+        cursor.insert_after(11, -3);
+        assert_eq!(cursor.key(), Some(&10));
+        assert_eq!(cursor.peek_prev(), Some(&(8, -2)));
+        assert_eq!(cursor.peek_next(), Some(&(11, -3)));
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some((&11, &mut -3)));
+
+        let mut cursor = tree.upper_bound_mut(Bound::Excluded(&0));
+        assert_eq!(cursor.index(), None);
+        cursor.insert_after(-4, 0);
+        cursor.insert_before(1000, 0);
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.replace_value(7), None);
+        assert_eq!(tree.first(), Some(&(-4, 0)));
+        assert_eq!(tree.last(), Some(&(1000, 0)));
+        assert_eq!(tree.size(), 54);
+    }
+
+    #[test]
+    #[should_panic]
+    fn cursor_mut_2() {
+        let mut tree: Tree234<i32, i32> = Tree234::new();
+        for i in 0..10 {
+            tree.insert(i * 2, i);
+        }
+        let mut cursor = tree.lower_bound_mut(Bound::Included(&4));
+        cursor.insert_after(7, 0);
+    }
+
+    #[test]
+    fn iterator_2() {
+        let mut tree: Tree234<u32, u32> = Tree234::new();
+        for n in 0..300u32 {
+            let mut xs: Vec<u32> = Vec::new();
+            tree.visit(&mut |item| xs.push(item.0));
+            let ys = tree.keys().cloned().collect::<Vec<u32>>();
+            assert_eq!(ys, xs);
+            let mut zs = tree.keys().rev().cloned().collect::<Vec<u32>>();
+            zs.reverse();
+            assert_eq!(zs, xs);
+            assert_eq!(tree.iter().len(), n as usize);
+            tree.insert(n.wrapping_mul(0x9e3779b9) >> 8, n);
+        }
+    }
+
+    #[test]
+    fn split_join_1() {
+        let mut rng = StdRng::seed_from_u64(41u64);
+        for n in [0, 1, 2, 3, 5, 10, 50, 200, 1000] {
+            let mut tree: Tree234<u64, u64> = Tree234::new();
+            for i in 0..n {
+                tree.insert((rng.gen::<u64>() & 0xfff) * 2, i);
+            }
+            let xs = tree.keys().cloned().collect::<Vec<u64>>();
+            for _i in 0..20 {
+                let mut lhs: Tree234<u64, u64> = Tree234::new();
+                for x in xs.iter() {
+                    lhs.insert(*x, *x);
+                }
+                let key = rng.gen::<u64>() & 0x1fff;
+                let rhs = lhs.split_off(&key);
+                check_tree(&lhs);
+                check_tree(&rhs);
+                let ls = lhs.keys().cloned().collect::<Vec<u64>>();
+                let rs = rhs.keys().cloned().collect::<Vec<u64>>();
+                assert_eq!(
+                    ls,
+                    xs.iter()
+                        .cloned()
+                        .filter(|x| *x < key)
+                        .collect::<Vec<u64>>()
+                );
+                assert_eq!(
+                    rs,
+                    xs.iter()
+                        .cloned()
+                        .filter(|x| *x >= key)
+                        .collect::<Vec<u64>>()
+                );
+                let tree = lhs.join(rhs);
+                check_tree(&tree);
+                assert_eq!(tree.keys().cloned().collect::<Vec<u64>>(), xs);
+            }
+        }
+    }
+
+    #[test]
+    fn append_ordered_1() {
+        for n in 0..40 {
+            for m in [0, 1, 2, 7, 30, 100, 1000] {
+                let mut lhs: Tree234<u32, u32> = Tree234::new();
+                let mut rhs: Tree234<u32, u32> = Tree234::new();
+                for i in 0..n {
+                    lhs.insert(i, i);
+                }
+                for i in 0..m {
+                    rhs.insert(n + i, n + i);
+                }
+                lhs.append_ordered(rhs);
+                check_tree(&lhs);
+                assert_eq!(lhs.size(), (n + m) as usize);
+                assert_eq!(
+                    lhs.keys().cloned().collect::<Vec<u32>>(),
+                    (0..n + m).collect::<Vec<u32>>()
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn append_ordered_2() {
+        let mut lhs: Tree234<u32, u32> = Tree234::new();
+        let mut rhs: Tree234<u32, u32> = Tree234::new();
+        lhs.insert(5, 5);
+        rhs.insert(5, 5);
+        lhs.append_ordered(rhs);
+    }
+
+    #[test]
+    fn remove_range_1() {
+        let mut rng = StdRng::seed_from_u64(43u64);
+        let mut xs = Vec::new();
+        let mut tree: Tree234<u32, u32> = Tree234::new();
+        for i in 0..500 {
+            xs.push(i * 2);
+            tree.insert(i * 2, i);
+        }
+        for _i in 0..200 {
+            let a = rng.gen::<u32>() % 1010;
+            let b = rng.gen::<u32>() % 1010;
+            let r = match rng.gen::<u32>() % 4 {
+                0 => (Bound::Included(a), Bound::Excluded(b)),
+                1 => (Bound::Excluded(a), Bound::Included(b)),
+                2 => (Bound::Unbounded, Bound::Included(b)),
+                _ => (Bound::Included(a), Bound::Unbounded),
+            };
+            let mut other = Tree234::new();
+            let mut removed = Tree234::new();
+            for x in xs.iter() {
+                other.insert(*x, *x);
+                removed.insert(*x, *x);
+            }
+            let inside = xs
+                .iter()
+                .cloned()
+                .filter(|x| r.contains(x))
+                .collect::<Vec<u32>>();
+            let outside = xs
+                .iter()
+                .cloned()
+                .filter(|x| !r.contains(x))
+                .collect::<Vec<u32>>();
+            removed.remove_range(r);
+            check_tree(&removed);
+            assert_eq!(removed.keys().cloned().collect::<Vec<u32>>(), outside);
+            let drained = other.drain_range(r);
+            assert_eq!(drained.len(), inside.len());
+            assert_eq!(drained.map(|item| item.0).collect::<Vec<u32>>(), inside);
+            check_tree(&other);
+            assert_eq!(other.keys().cloned().collect::<Vec<u32>>(), outside);
+        }
+        tree.remove_range(..);
+        assert_eq!(tree.size(), 0);
+    }
+
+    #[test]
+    fn bulk_build_1() {
+        for n in 0..300 {
+            let tree = Tree234::from_sorted_iter((0..n).map(|i| (i, i * 2))).unwrap();
+            let height = check_node(&tree.root);
+            assert_eq!(tree.size(), n);
+            assert!(n < 4usize.pow(height as u32));
+            assert!(n == 0 || n >= 4usize.pow(height as u32 - 1));
+            let xs = tree.iter().cloned().collect::<Vec<(usize, usize)>>();
+            assert_eq!(
+                xs,
+                (0..n).map(|i| (i, i * 2)).collect::<Vec<(usize, usize)>>()
+            );
+        }
+        assert_eq!(
+            Tree234::from_sorted_iter([(1, 1), (3, 3), (2, 2)]).err(),
+            Some(BuildError::Unsorted { index: 2 })
+        );
+        assert_eq!(
+            Tree234::from_sorted_iter([(1, 1), (1, 2)]).err(),
+            Some(BuildError::Duplicate { index: 1 })
+        );
+    }
+
+    #[test]
+    fn bulk_build_2() {
+        let mut rng = StdRng::seed_from_u64(47u64);
+        let mut items = Vec::new();
+        let mut tree = Tree234::new();
+        for i in 0..5000u32 {
+            let x = rng.gen::<u32>() & 0x3ff;
+            items.push((x, i));
+            tree.insert(x, i);
+        }
+        let built = Tree234::from_unsorted_vec(items);
+        check_tree(&built);
+        assert_eq!(
+            built.iter().collect::<Vec<&(u32, u32)>>(),
+            tree.iter().collect::<Vec<&(u32, u32)>>()
+        );
+    }
+
+    #[test]
+    fn traits_1() {
+        let tree: Tree234<u32, u32> = Default::default();
+        assert_eq!(tree.size(), 0);
+        let tree = (0..100).map(|i| (i, i * 3)).collect::<Tree234<u32, u32>>();
+        check_tree(&tree);
+        assert_eq!(tree.size(), 100);
+        assert_eq!(tree[&7], 21);
+        let tree = [(3, 1), (1, 2), (3, 3), (2, 4)]
+            .into_iter()
+            .collect::<Tree234<u32, u32>>();
+        check_tree(&tree);
+        assert_eq!(
+            tree.iter().cloned().collect::<Vec<(u32, u32)>>(),
+            vec![(1, 2), (2, 4), (3, 3)]
+        );
+        let mut tree = Tree234::from([(5, 'e'), (1, 'a')]);
+        tree.extend([(6, 'f'), (7, 'g')]);
+        tree.extend([(3, 'c'), (2, 'b'), (7, 'G')]);
+        tree.extend([(&4, &'d')]);
+        check_tree(&tree);
+        assert_eq!(tree.values().collect::<String>(), "abcdefG");
+    }
+
+    #[test]
+    #[should_panic]
+    fn traits_2() {
+        let tree = Tree234::from([(1, 1)]);
+        let _ = tree[&2];
+    }
+
+    #[test]
+    fn comparisons_1() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let hash = |tree: &Tree234<u32, u32>| {
+            let mut hasher = DefaultHasher::new();
+            tree.hash(&mut hasher);
+            hasher.finish()
+        };
+        let mut lhs = Tree234::new();
+        for i in 0..100 {
+            lhs.insert(i, i);
+        }
+        let rhs = (0..100).map(|i| (i, i)).collect::<Tree234<u32, u32>>();
+        assert_eq!(lhs, rhs);
+        assert_eq!(hash(&lhs), hash(&rhs));
+        let copy = lhs.clone();
+        check_tree(&copy);
+        assert_eq!(copy, lhs);
+        assert_eq!(copy.root.len(), lhs.root.len());
+        lhs.insert(50, 0);
+        assert!(lhs < rhs);
+        assert_eq!(lhs.cmp(&rhs), std::cmp::Ordering::Less);
+        lhs.remove(&99);
+        assert!(lhs != rhs);
+        assert!(hash(&lhs) != hash(&rhs));
+        let tree = Tree234::from([(2, "b"), (1, "a")]);
+        assert_eq!(format!("{:?}", tree), r#"{1: "a", 2: "b"}"#);
+    }
+
+    #[test]
+    fn borrow_1() {
+        let mut tree: Tree234<String, u32> = Tree234::new();
+        for (i, word) in ["pear", "apple", "fig", "plum", "kiwi"].iter().enumerate() {
+            tree.insert(word.to_string(), i as u32);
+        }
+        assert_eq!(tree.get("fig"), Some(&("fig".to_string(), 2)));
+        assert!(tree.contains_key("kiwi"));
+        assert!(!tree.contains_key("lime"));
+        assert_eq!(tree["plum"], 3);
+        assert_eq!(tree.rank("lime"), 3);
+        assert_eq!(tree.floor("lime").map(|item| item.1), Some(4));
+        assert_eq!(tree.successor("pear").map(|item| item.1), Some(3));
+        assert_eq!(
+            tree.range::<str, _>((Bound::Included("b"), Bound::Excluded("p")))
+                .map(|item| item.0.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["fig", "kiwi"]
+        );
+        assert_eq!(
+            tree.lower_bound(Bound::Excluded("kiwi"))
+                .key()
+                .map(|k| k.as_str()),
+            Some("pear")
+        );
+        assert_eq!(tree.remove("apple"), Some(1));
+        let rest = tree.split_off("l");
+        assert_eq!(tree.size(), 2);
+        assert_eq!(rest.size(), 2);
+
+        let mut tree: Tree234<Vec<u8>, u32> = Tree234::new();
+        tree.insert(b"abc".to_vec(), 1);
+        tree.insert(b"abd".to_vec(), 2);
+        assert_eq!(tree.get_mut(&b"abd"[..]).map(|v| *v), Some(2));
+        assert_eq!(tree.remove_entry(&b"abc"[..]), Some((b"abc".to_vec(), 1)));
+        tree.remove_range::<[u8], _>((Bound::Unbounded, Bound::Included(&b"abd"[..])));
+        assert_eq!(tree.size(), 0);
+    }
+
+    #[test]
+    fn merge_1() {
+        let mut rng = StdRng::seed_from_u64(53u64);
+        let mut xs = std::collections::BTreeMap::new();
+        let mut ys = std::collections::BTreeMap::new();
+        for i in 0..2000u32 {
+            xs.insert(rng.gen::<u32>() & 0x7ff, i);
+            ys.insert(rng.gen::<u32>() & 0x7ff, i + 10000);
+        }
+        let tree = |map: &std::collections::BTreeMap<u32, u32>| {
+            map.iter()
+                .map(|(k, v)| (*k, *v))
+                .collect::<Tree234<u32, u32>>()
+        };
+        let union = tree(&xs).union_with(tree(&ys), |_k, a, b| a + b);
+        check_tree(&union);
+        let mut expected = ys.clone();
+        for (k, v) in xs.iter() {
+            *expected.entry(*k).or_insert(0) += *v;
+        }
+        assert_eq!(
+            union
+                .iter()
+                .map(|item| (item.0, item.1))
+                .collect::<Vec<(u32, u32)>>(),
+            expected.into_iter().collect::<Vec<(u32, u32)>>()
+        );
+        let intersection = tree(&xs).intersection_with(tree(&ys), |k, a, b| k ^ a ^ b);
+        check_tree(&intersection);
+        let expected = xs
+            .iter()
+            .filter_map(|(k, v)| ys.get(k).map(|w| (*k, k ^ v ^ w)))
+            .collect::<Vec<(u32, u32)>>();
+        assert_eq!(
+            intersection.into_iter().collect::<Vec<(u32, u32)>>(),
+            expected
+        );
+        let difference = tree(&xs).difference(tree(&ys));
+        check_tree(&difference);
+        let expected = xs
+            .iter()
+            .filter(|(k, _)| !ys.contains_key(k))
+            .map(|(k, v)| (*k, *v))
+            .collect::<Vec<(u32, u32)>>();
+        assert_eq!(
+            difference.into_iter().collect::<Vec<(u32, u32)>>(),
+            expected
+        );
+        let symmetric = tree(&xs).symmetric_difference(tree(&ys));
+        check_tree(&symmetric);
+        let mut expected = xs
+            .iter()
+            .filter(|(k, _)| !ys.contains_key(k))
+            .chain(ys.iter().filter(|(k, _)| !xs.contains_key(k)))
+            .map(|(k, v)| (*k, *v))
+            .collect::<Vec<(u32, u32)>>();
+        expected.sort();
+        assert_eq!(symmetric.into_iter().collect::<Vec<(u32, u32)>>(), expected);
+    }
+
+    #[test]
+    fn retain_1() {
+        let mut tree = (0..1000u32).map(|i| (i, i)).collect::<Tree234<u32, u32>>();
+        tree.retain(|k, v| {
+            *v += 1;
+            k % 3 != 0
+        });
+        check_tree(&tree);
+        assert_eq!(tree.size(), 666);
+        assert!(tree
+            .iter()
+            .all(|item| item.0 % 3 != 0 && item.1 == item.0 + 1));
+
+        let mut extracted = tree.extract_if(|k, _v| k % 2 == 0);
+        assert_eq!(extracted.next(), Some((2, 3)));
+        assert_eq!(extracted.next(), Some((4, 5)));
+        drop(extracted);
+        check_tree(&tree);
+        assert_eq!(tree.size(), 664);
+        assert!(!tree.contains_key(&4));
+        assert!(tree.contains_key(&8));
+
+        let extracted = tree
+            .extract_if(|k, _v| k % 2 == 0)
+            .collect::<Vec<(u32, u32)>>();
+        assert_eq!(extracted.len(), 331);
+        check_tree(&tree);
+        assert_eq!(tree.size(), 333);
+        assert!(tree.keys().all(|k| k % 2 == 1 && k % 3 != 0));
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Sum(u64);
+
+    impl Augment<u32, u32> for Sum {
+        fn identity() -> Sum {
+            Sum(0)
+        }
+
+        fn from_item(_key: &u32, value: &u32) -> Sum {
+            Sum(*value as u64)
+        }
+
+        fn combine(&self, other: &Sum) -> Sum {
+            Sum(self.0 + other.0)
+        }
+    }
+
+    // The first and last keys, which is only correct if combined in order.
+    #[derive(Clone, Debug, PartialEq)]
+    struct Span(Option<(u32, u32)>);
+
+    impl Augment<u32, u32> for Span {
+        fn identity() -> Span {
+            Span(None)
+        }
+
+        fn from_item(key: &u32, _value: &u32) -> Span {
+            Span(Some((*key, *key)))
+        }
+
+        fn combine(&self, other: &Span) -> Span {
+            match (self.0, other.0) {
+                (Some((first, _)), Some((_, last))) => Span(Some((first, last))),
+                (lhs, rhs) => Span(lhs.or(rhs)),
+            }
+        }
+    }
+
+    #[test]
+    fn aggregate_1() {
+        let mut rng = StdRng::seed_from_u64(59u64);
+        let mut sums: Tree234<u32, u32, Sum> = Tree234::default();
+        let mut spans: Tree234<u32, u32, Span> = Tree234::default();
+        let mut xs = std::collections::BTreeMap::new();
+        for i in 0..3000u32 {
+            let key = rng.gen::<u32>() & 0x3ff;
+            if rng.gen::<u32>() % 4 == 0 {
+                sums.remove(&key);
+                spans.remove(&key);
+                xs.remove(&key);
+            } else {
+                sums.insert(key, i);
+                spans.insert(key, i);
+                xs.insert(key, i);
+            }
+            if i % 100 == 0 {
+                let lhs = sums.split_off(&key);
+                check_tree(&sums);
+                sums.append_ordered(lhs);
+                spans.remove_range(key..key + 10);
+                sums.remove_range(key..key + 10);
+                let doomed = xs
+                    .range(key..key + 10)
+                    .map(|(k, _)| *k)
+                    .collect::<Vec<u32>>();
+                for k in doomed {
+                    xs.remove(&k);
+                }
+            }
+        }
+        for _i in 0..500 {
+            let a = rng.gen::<u32>() & 0x3ff;
+            let b = rng.gen::<u32>() & 0x3ff;
+            let r = match rng.gen::<u32>() % 3 {
+                0 => (Bound::Included(a), Bound::Excluded(b)),
+                1 => (Bound::Excluded(a), Bound::Included(b)),
+                _ => (Bound::Unbounded, Bound::Included(b)),
+            };
+            if a > b {
+                continue;
+            }
+            let expected = xs.range(r).map(|(_, v)| *v as u64).sum::<u64>();
+            assert_eq!(sums.aggregate(r), Sum(expected));
+            let keys = xs.range(r).map(|(k, _)| *k).collect::<Vec<u32>>();
+            let expected = keys.first().map(|first| (*first, *keys.last().unwrap()));
+            assert_eq!(spans.aggregate(r), Span(expected));
+        }
+        assert_eq!(
+            sums.aggregate(..),
+            Sum(xs.values().map(|v| *v as u64).sum())
+        );
+        sums.retain(|k, _v| k % 2 == 0);
+        let expected = xs
+            .iter()
+            .filter(|(k, _)| *k % 2 == 0)
+            .map(|(_, v)| *v as u64)
+            .sum();
+        assert_eq!(sums.aggregate(..), Sum(expected));
+        let built = xs
+            .iter()
+            .map(|(k, v)| (*k, *v))
+            .collect::<Tree234<u32, u32, Span>>();
+        let expected = Span(Some((
+            *xs.keys().next().unwrap(),
+            *xs.keys().last().unwrap(),
+        )));
+        assert_eq!(built.aggregate(..), expected);
+    }
+
+    #[test]
+    fn in_place_1() {
+        let mut rng = StdRng::seed_from_u64(67u64);
+        let mut tree: Tree234<u32, u32, Sum> = Tree234::default();
+        let mut model = std::collections::BTreeMap::new();
+        for i in 0..5000u32 {
+            let k = rng.gen::<u32>() & 0xff;
+            match rng.gen::<u32>() % 8 {
+                0 => assert_eq!(tree.pop_first(), model.pop_first()),
+                1 => assert_eq!(tree.pop_last(), model.pop_last()),
+                2..=4 => assert_eq!(tree.remove(&k), model.remove(&k)),
+                _ => assert_eq!(tree.insert(k, i), model.insert(k, i)),
+            }
+            check_tree(&tree);
+            assert_eq!(tree.size(), model.len());
+            let total = model.values().map(|v| *v as u64).sum::<u64>();
+            assert_eq!(tree.aggregate::<u32, _>(..), Sum(total));
+        }
+    }
+
+    #[test]
+    fn remove_2() {
+        let mut rng = StdRng::seed_from_u64(61u64);
+        for n in 1..200u32 {
+            for _t in 0..3 {
+                let mut keys = (0..n).collect::<Vec<u32>>();
+                keys.shuffle(&mut rng);
+                let mut tree: Tree234<u32, u32> = Tree234::new();
+                for k in keys.iter() {
+                    tree.insert(*k, *k);
+                }
+                keys.shuffle(&mut rng);
+                for (i, k) in keys.iter().enumerate() {
+                    assert_eq!(tree.remove(k), Some(*k));
+                    assert_eq!(tree.size(), keys.len() - i - 1);
+                    for rest in keys[i + 1..].iter() {
+                        assert_eq!(tree.get(rest), Some(&(*rest, *rest)));
+                    }
+                }
+            }
+        }
+    }
+
     #[test]
     fn structures_1() {
         let mut rng = StdRng::seed_from_u64(17u64);
@@ -1685,10 +4382,12 @@ mod tests {
                 let y: u64 = rng.gen::<u64>() & 0xffffff;
                 tree.insert(y, j as u64);
             }
+            check_tree(&tree);
             xs.shuffle(&mut rng);
             for x in xs.iter() {
                 tree.remove(x);
             }
+            check_tree(&tree);
         }
     }
 